@@ -1,4 +1,7 @@
+use crate::container::{Container, SimplificationStep};
 use crate::util::PrettyPrint;
+use crate::validation::StatusError;
+use crate::validation::StatusError::Known;
 use serde::{Deserialize, Serialize};
 
 /// Possible Component Types
@@ -14,6 +17,7 @@ pub enum Component {
     Switch,
     Inductor,
     Capacitor,
+    Diode,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -25,6 +29,52 @@ pub enum Simplification {
     Thevinin,
 }
 
+impl Simplification {
+    /// Apply the reduction to a container, returning how many elements were merged away.
+    ///
+    /// Series merges resistors and voltage sources joined by a node of their own, Parallel
+    /// merges resistors across the same pair of nodes, see `Container::simplify`. Tools are
+    /// cleared when anything is merged and have to be created again.
+    pub fn simplify(&self, container: &mut Container) -> Result<usize, StatusError> {
+        let merged = |steps: Vec<SimplificationStep>| -> usize {
+            steps.iter().map(|x| x.element_ids.len() - 1).sum()
+        };
+        match self {
+            Simplification::None => Ok(0),
+            Simplification::Series => {
+                let sources: usize = container.collapse_series_sources();
+                Ok(sources + merged(container.reduce_resistors(Some(self))))
+            }
+            Simplification::Parallel => Ok(merged(container.reduce_resistors(Some(self)))),
+            other => Err(Known(format!(
+                "{:?} simplification is not supported",
+                other
+            ))),
+        }
+    }
+}
+
+/// Time dependence of a source, the DC solvers only use the element value
+///
+/// The element value is the DC level or the amplitude of the waveform.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum WaveformType {
+    DC,
+    Sine {
+        freq: f64,  // Hz
+        phase: f64, // rad
+    },
+    Pulse {
+        low: f64,
+        high: f64,
+        delay: f64, // s
+        rise: f64,
+        fall: f64,
+        width: f64,
+        period: f64,
+    },
+}
+
 impl Component {
     pub(crate) fn unit_string(&self) -> String {
         match self {
@@ -32,6 +82,7 @@ impl Component {
             Component::Resistor => "Ω".to_string(),
             Component::VoltageSrc => "V".to_string(),
             Component::CurrentSrc => "A".to_string(),
+            Component::Diode => "A".to_string(),
             _ => "Unknown".to_string(),
         }
     }
@@ -52,6 +103,7 @@ impl PrettyPrint for Component {
             Component::Resistor => "Resistor".to_string(),
             Component::VoltageSrc => "Voltage".to_string(),
             Component::CurrentSrc => "Current".to_string(),
+            Component::Diode => "Diode".to_string(),
             _ => "Unknown".to_string(),
         }
     }
@@ -62,6 +114,7 @@ impl PrettyPrint for Component {
             Component::Resistor => "R".to_string(),
             Component::VoltageSrc => "SRC(V)".to_string(),
             Component::CurrentSrc => "SRC(C)".to_string(),
+            Component::Diode => "D".to_string(),
             _ => "Unknown".to_string(),
         }
     }
@@ -1,20 +1,27 @@
-use crate::component::Component::{Ground, VoltageSrc};
-use crate::component::Simplification;
-use crate::elements::Element;
+use crate::component::Component::{CurrentSrc, Ground, Resistor, VoltageSrc};
+use crate::component::{Component, Simplification};
+use crate::elements::{Element, TYPICAL_RESISTANCE};
+use crate::kicad::parse_netlist;
+use crate::planarity;
+use crate::solvers::solver::{Step, SubStep};
 use crate::tools::{Tool, ToolType};
 use crate::util::PrettyPrint;
 use crate::validation::StatusError::Known;
 use crate::validation::{
     check_duplicates, get_all_internal_status_errors, Status, StatusError, Validation,
-    ValidationResult,
+    ValidationMode, ValidationResult,
 };
+use nalgebra::DMatrix;
+use operations::math::EquationMember;
 use petgraph::graph::UnGraph;
 use petgraph::prelude::NodeIndex;
 use rustworkx_core::connectivity;
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 
 use crate::tools::ToolType::SuperNode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::fmt::{Debug, Formatter};
 use std::rc::{Rc, Weak};
 
@@ -24,9 +31,99 @@ use std::rc::{Rc, Weak};
 #[derive(Clone, Serialize)]
 pub struct Container {
     elements: Vec<Rc<RefCell<Element>>>,
+    #[serde(skip)]
+    index: HashMap<usize, usize>, // Element id -> position in elements
     tools: Vec<Rc<RefCell<Tool>>>,
     simplifications: Vec<Rc<Simplification>>,
     ground: usize,
+    reference: Option<usize>,
+    ground_voltage: f64,
+    naming: NamingScheme,
+}
+
+/// How elements added to a Container are numbered in their names
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub enum NamingScheme {
+    /// The element id, R3 is the element with id 3
+    #[default]
+    GlobalId,
+    /// Count per class, R2 is the second resistor
+    PerClassSequential,
+}
+
+/// Serialized shape of a Container, see `Container::to_json`
+#[derive(Deserialize)]
+struct ContainerJson {
+    elements: Vec<Element>,
+    #[serde(default)]
+    ground: usize,
+}
+
+/// Node voltage statistics from `Container::monte_carlo`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MonteCarloResult {
+    pub samples: usize, // Samples that solved
+    pub nodes: Vec<usize>,
+    pub mean: Vec<f64>,
+    pub std_dev: Vec<f64>,
+}
+
+/// Resistors that could be replaced by a single one, see `Container::simplification_opportunities`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimplificationOpportunity {
+    pub kind: Simplification, // Series or Parallel
+    pub element_ids: Vec<usize>,
+    pub equivalent: f64, // Ω
+}
+
+/// A reduction applied by `Container::simplify`, ids are as they were when it ran
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimplificationStep {
+    pub kind: Simplification, // Series or Parallel
+    pub element_ids: Vec<usize>,
+    pub kept: usize, // Resistor that took the equivalent value, the others were removed
+    pub equivalent: f64, // Ω
+}
+
+/// Thevenin and Norton equivalents seen by a load, see `Container::port_analysis`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PortResult {
+    pub thevenin_voltage: f64,    // V, open circuit across the load's node pair
+    pub thevenin_resistance: f64, // Ω
+    pub norton_current: f64,      // A, infinite behind an ideal voltage source
+    pub load_voltage: f64,        // V
+    pub load_current: f64,        // A
+}
+
+/// Common circuit shape recognized by `Container::classify_topology`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TopologyHint {
+    /// A single loop of resistors around one voltage source
+    VoltageDivider,
+    /// Four resistors in a square with a fifth across the middle, fed across the other diagonal
+    WheatstoneBridge,
+    /// Series resistors along a chain of nodes, each node past the source shunted to ground
+    ResistorLadder,
+    /// None of the shapes above
+    Unrecognized,
+}
+
+/// Seed for `Container::monte_carlo`, fixed so runs are reproducible
+const MONTE_CARLO_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// Saved element and ground state of a Container, see `Container::snapshot`
+#[derive(Clone, Debug)]
+pub struct ContainerSnapshot {
+    elements: Vec<Element>,
+    ground: usize,
+    reference: Option<usize>,
+}
+
+/// Breadth first spanning tree of the node graph from ground, see `Container::spanning_tree`
+struct SpanningTree {
+    parent: Vec<Option<(usize, usize)>>, // (Pair index, Parent node) for each node
+    visited: Vec<bool>,
+    in_tree: Vec<bool>, // For each node pair
 }
 
 /// Container is a collection of Elements and Tools we are using to solve the circuit
@@ -38,44 +135,192 @@ impl Container {
     pub(crate) fn new() -> Container {
         Container {
             elements: Vec::new(),
+            index: HashMap::new(),
             tools: Vec::new(),
             simplifications: vec![],
             ground: 0,
+            reference: None,
+            ground_voltage: 0.0,
+            naming: NamingScheme::GlobalId,
+        }
+    }
+
+    /// Build a Container from elements that bring their own ids.
+    ///
+    /// Connections refer to those ids, so they are kept when they are all distinct. Elements
+    /// built with `Element::new` leave every id at 0 and are numbered by position instead.
+    pub(crate) fn from_elements(elements: Vec<Element>) -> Container {
+        let mut ids: Vec<usize> = elements.iter().map(|x| x.id).collect();
+        ids.sort();
+        ids.dedup();
+        let keep_ids: bool = ids.len() == elements.len();
+
+        let mut container = Container::new();
+        for element in elements {
+            match keep_ids {
+                true => container.add_element_core(element),
+                false => container.add_element_no_id(element),
+            };
+        }
+        if let Some(ground) = container
+            .elements
+            .iter()
+            .find(|x| x.borrow().class == Ground)
+        {
+            container.ground = ground.borrow().id;
+        }
+        container
+    }
+
+    /// Ladder of series and shunt resistors fed by a voltage source, for benchmarks and demos.
+    ///
+    /// Stage k is the series resistor `2 + 2k` followed by the shunt resistor `3 + 2k` to
+    /// ground, at least one stage is built.
+    pub fn resistor_ladder(stages: usize, r: f64, source_v: f64) -> Container {
+        let stages: usize = stages.max(1);
+        let shunts: Vec<usize> = (0..stages).map(|k| 3 + 2 * k).collect();
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(
+            Ground,
+            0.,
+            [vec![1], shunts.clone()].concat(),
+            vec![],
+        ));
+        container.add_element_no_id(Element::new(VoltageSrc, source_v, vec![2], vec![0]));
+        for k in 0..stages {
+            let (series, shunt): (usize, usize) = (2 + 2 * k, 3 + 2 * k);
+            let before: Vec<usize> = match k {
+                0 => vec![1],
+                _ => vec![series - 2, series - 1],
+            };
+            let next: Vec<usize> = match k + 1 < stages {
+                true => vec![series + 2],
+                false => vec![],
+            };
+            container.add_element_no_id(Element::new(
+                Resistor,
+                r,
+                before,
+                [vec![shunt], next.clone()].concat(),
+            ));
+            container.add_element_no_id(Element::new(
+                Resistor,
+                r,
+                [vec![series], next].concat(),
+                vec![0],
+            ));
+        }
+        container
+    }
+
+    /// Build a Container from (node_a, node_b, component, value) edges, node 0 is ground.
+    ///
+    /// Every edge becomes an element with its positive side on node_a, in edge order after
+    /// the ground element.
+    pub fn from_adjacency(
+        edges: Vec<(usize, usize, Component, f64)>,
+    ) -> Result<Container, StatusError> {
+        // Element ids start at 1, ground takes id 0
+        let terminals_at = |node: usize, except: usize| -> Vec<usize> {
+            match node {
+                0 => vec![0],
+                _ => edges
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, (a, b, _, _))| k + 1 != except && (*a == node || *b == node))
+                    .map(|(k, _)| k + 1)
+                    .collect(),
+            }
+        };
+
+        let grounded: Vec<usize> = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, (a, b, _, _))| *a == 0 || *b == 0)
+            .map(|(k, _)| k + 1)
+            .collect();
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0., grounded, vec![]));
+        for (k, (a, b, class, value)) in edges.iter().enumerate() {
+            container.add_element_no_id(Element::new(
+                class.clone(),
+                *value,
+                terminals_at(*a, k + 1),
+                terminals_at(*b, k + 1),
+            ));
         }
+        container.validate()?;
+        Ok(container)
+    }
+
+    /// Choose how elements added from now on are numbered.
+    pub fn set_naming_scheme(&mut self, naming: NamingScheme) -> &mut Self {
+        self.naming = naming;
+        self
     }
 
     /// Add an Element to the Container
     ///
     /// This function will add an Element to the Container and return the index of the Element
     pub fn add_element(&mut self, mut element: Element) -> Result<usize, StatusError> {
-        element.id = self.elements.len();
+        element.id = self.next_id();
+        element.normalize_connections();
         element.validate()?;
         let id: usize = self.add_element_no_id(element);
         let check = self.validate();
         if check.is_err() {
             self.elements.pop();
+            self.index.remove(&id);
             return Err(check.unwrap_err());
         }
         Ok(id)
     }
 
     pub(crate) fn add_element_no_id(&mut self, mut element: Element) -> usize {
-        let id: usize = self.elements.len();
-        if element.name == "" {
-            element.name = element.class.basic_string();
-        }
-        element.id = id;
+        element.id = self.next_id();
+        self.add_element_core(element)
+    }
+
+    pub(crate) fn add_element_core(&mut self, mut element: Element) -> usize {
+        self.assign_name(&mut element);
+        let id = element.id.clone();
+        self.index.insert(id, self.elements.len());
         self.elements.push(Rc::new(RefCell::new(element)));
         id
     }
 
-    pub(crate) fn add_element_core(&mut self, mut element: Element) -> usize {
+    /// One past the largest element id, the id of positional elements is their position.
+    fn next_id(&self) -> usize {
+        self.elements
+            .iter()
+            .map(|x| x.borrow().id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Rebuild the id lookup after the element list was replaced or reordered.
+    fn reindex(&mut self) {
+        self.index = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.borrow().id, i))
+            .collect();
+    }
+
+    /// Class prefix for unnamed elements and the number the naming scheme gives it.
+    fn assign_name(&self, element: &mut Element) {
         if element.name == "" {
             element.name = element.class.basic_string();
         }
-        let id = element.id.clone();
-        self.elements.push(Rc::new(RefCell::new(element)));
-        id
+        if self.naming == NamingScheme::PerClassSequential {
+            let count: usize = self
+                .elements
+                .iter()
+                .filter(|x| x.borrow().class == element.class)
+                .count();
+            element.number = Some(count + 1);
+        }
     }
 
     fn add_tool(&mut self, mut tool: Tool) {
@@ -88,11 +333,20 @@ impl Container {
         self.tools.push(Rc::new(RefCell::new(tool)));
     }
 
-    pub(crate) fn get_element_by_id(&self, id: usize) -> &Rc<RefCell<Element>> {
-        match self.elements.get(id) {
-            Some(element) => element,
-            None => panic!("Element with id {} does not exist", id),
-        }
+    /// Element whose id field matches, which need not be its position when the ids come
+    /// from the user.
+    pub fn get_element_by_id(&self, id: usize) -> Option<&Rc<RefCell<Element>>> {
+        self.index.get(&id).and_then(|x| self.elements.get(*x))
+    }
+
+    /// Position of a node Tool among `nodes` and the solved voltages, None for ground.
+    ///
+    /// Node tools take ids from 1 in the order they are created, an id past the last node
+    /// is None as well.
+    pub fn node_index(&self, tool_id: usize) -> Option<usize> {
+        tool_id
+            .checked_sub(1)
+            .filter(|index| *index < self.nodes().len())
     }
 
     pub(crate) fn get_tool_by_id(&self, id: usize) -> &Rc<RefCell<Tool>> {
@@ -125,45 +379,119 @@ impl Container {
     /// comparing the samples to see if they are the same. If they are the same
     /// then they are connected and should be added to the same node.
     /// By by filtering our duplicates we can create a pure list of nodes.
+    ///
+    /// Existing nodes are replaced so calling this again rebuilds the same nodes, a rebuilt
+    /// node keeps the label of the node it replaces. Other tools such as supernodes are kept
+    /// and numbered after the nodes.
     pub fn create_nodes(&mut self) -> Result<&mut Self, StatusError> {
+        let (old_nodes, others): (Vec<Rc<RefCell<Tool>>>, Vec<Rc<RefCell<Tool>>>) = self
+            .tools
+            .drain(..)
+            .partition(|x| x.borrow().class == ToolType::Node);
         let mut new_nodes: Vec<Tool> = Vec::new();
 
-        for element in &self.elements {
+        for element in self.elements.iter().filter(|x| x.borrow().enabled) {
             // Need a list of all elements connected to the positive side node.
-            let mut node_elements: Vec<Weak<RefCell<Element>>> = element
-                .borrow()
-                .positive
-                .iter()
-                .map(|positive_id: &usize| self.get_element_by_id(*positive_id))
-                .map(|x| Rc::downgrade(x))
-                .collect();
-            node_elements.push(Rc::downgrade(element)); // Include the element itself
+            let positive: Vec<usize> = element.borrow().positive.clone();
+            let node_elements: Vec<Weak<RefCell<Element>>> = self.node_elements(element, &positive);
 
             let ground: bool = node_elements
                 .iter()
                 .any(|x| x.upgrade().unwrap().borrow().class == Ground);
             let duplicate: bool = new_nodes.iter().any(|x| x.contains_all(&node_elements));
-            let duplicate_node: bool = self.tools.iter().any(|x| {
-                if x.borrow().class == ToolType::Node {
-                    x.borrow().contains_all(&node_elements)
-                } else {
-                    false
-                }
-            });
 
-            if ground || duplicate || duplicate_node {
+            if ground || duplicate {
                 continue;
             }
             new_nodes.push(Tool::create_node(node_elements));
         }
 
-        for node in new_nodes {
+        let sorted_members = |tool: &Tool| -> Vec<usize> {
+            let mut ids: Vec<usize> = tool.member_ids();
+            ids.sort();
+            ids
+        };
+        for mut node in new_nodes {
+            node.label = old_nodes
+                .iter()
+                .find(|x| sorted_members(&x.borrow()) == sorted_members(&node))
+                .and_then(|x| x.borrow().label.clone());
             self.add_tool(node);
         }
+        for tool in others {
+            let id: usize = self.tools.last().map_or(1, |x| x.borrow().id + 1);
+            tool.borrow_mut().id = id;
+            self.tools.push(tool);
+        }
 
         Ok(self)
     }
 
+    /// Create or grow the nodes on both sides of a single element.
+    ///
+    /// Meant for incremental edits after `add_element`, an existing node that the element
+    /// joins gains the new members instead of being rebuilt. Returns the touched node ids.
+    pub fn add_node_for_element(&mut self, element_id: usize) -> Result<Vec<usize>, StatusError> {
+        let element: Rc<RefCell<Element>> = match self.get_element_by_id(element_id) {
+            Some(element) => element.clone(),
+            None => return Err(Known(format!("Element {} does not exist", element_id))),
+        };
+
+        let mut touched: Vec<usize> = Vec::new();
+        if !element.borrow().enabled {
+            return Ok(touched);
+        }
+        let sides: [Vec<usize>; 2] = [
+            element.borrow().positive.clone(),
+            element.borrow().negative.clone(),
+        ];
+        for side in sides.iter().filter(|x| !x.is_empty()) {
+            let node_elements: Vec<Weak<RefCell<Element>>> = self.node_elements(&element, side);
+            if node_elements
+                .iter()
+                .any(|x| x.upgrade().unwrap().borrow().class == Ground)
+            {
+                continue;
+            }
+
+            let existing: Option<Rc<RefCell<Tool>>> = self
+                .tools
+                .iter()
+                .find(|x| {
+                    x.borrow().class == ToolType::Node && x.borrow().contains_all(&node_elements)
+                })
+                .cloned();
+            match existing {
+                Some(node) => {
+                    node.borrow_mut().members = node_elements;
+                    touched.push(node.borrow().id);
+                }
+                None => {
+                    self.add_tool(Tool::create_node(node_elements));
+                    touched.push(self.tools.last().unwrap().borrow().id);
+                }
+            }
+        }
+
+        Ok(touched)
+    }
+
+    /// The element along with every enabled element listed in one of its connection lists.
+    fn node_elements(
+        &self,
+        element: &Rc<RefCell<Element>>,
+        connections: &[usize],
+    ) -> Vec<Weak<RefCell<Element>>> {
+        let mut node_elements: Vec<Weak<RefCell<Element>>> = connections
+            .iter()
+            .map(|id: &usize| self.get_element_by_id(*id).unwrap())
+            .filter(|x| x.borrow().enabled)
+            .map(|x| Rc::downgrade(x))
+            .collect();
+        node_elements.push(Rc::downgrade(element)); // Include the element itself
+        node_elements
+    }
+
     pub(crate) fn get_calculation_nodes(&self) -> Vec<Rc<RefCell<Tool>>> {
         let nodes: Vec<Rc<RefCell<Tool>>> =
             self.nodes().iter().map(|x| x.upgrade().unwrap()).collect();
@@ -199,13 +527,173 @@ impl Container {
         cleaned
     }
 
+    /// Ids of the base nodes a Tool stands for.
+    ///
+    /// A node stands for itself, a supernode for every node whose members it holds.
+    pub(crate) fn subsumed_nodes(&self, tool: &Tool) -> Vec<usize> {
+        if tool.class == ToolType::Node {
+            return vec![tool.id];
+        }
+        let member_ids: Vec<usize> = tool.member_ids();
+        self.nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .filter(|x| {
+                x.borrow()
+                    .member_ids()
+                    .iter()
+                    .all(|y| member_ids.contains(y))
+            })
+            .map(|x| x.borrow().id)
+            .collect()
+    }
+
+    /// Give a node a label such as VOUT to show in the steps in place of its id.
+    ///
+    /// The solvers keep using the id. `create_nodes` carries the label over to the rebuilt
+    /// node with the same members.
+    pub fn label_node(&mut self, node_id: usize, label: &str) -> Result<(), StatusError> {
+        let node = self
+            .nodes()
+            .into_iter()
+            .map(|x| x.upgrade().unwrap())
+            .find(|x| x.borrow().id == node_id);
+        match node {
+            None => Err(Known(format!("Node {} does not exist", node_id))),
+            Some(_) if label.trim().is_empty() => {
+                Err(Known("Node labels cannot be empty".to_string()))
+            }
+            Some(node) => {
+                node.borrow_mut().label = Some(label.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Each supernode id with the ids of the base nodes it groups together.
+    pub fn supernode_report(&self) -> Vec<(usize, Vec<usize>)> {
+        self.get_tools(SuperNode)
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .map(|x| {
+                let tool = x.borrow();
+                (tool.id, self.subsumed_nodes(&tool))
+            })
+            .collect()
+    }
+
+    /// How the elements were grouped into nodes and supernodes, for explaining node
+    /// identification ahead of the KCL. Call after `create_nodes`, and `create_super_nodes`
+    /// for the supernode step.
+    pub fn node_creation_steps(&self) -> Vec<Step> {
+        let names = |tool: &Tool| -> String {
+            let mut ids: Vec<usize> = tool.member_ids();
+            ids.sort();
+            ids.iter()
+                .map(|x| self.get_element_by_id(*x).unwrap().basic_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let mut steps: Vec<Step> = vec![Step {
+            title: Some("Identify Nodes".to_string()),
+            description: Some(
+                "Elements joined by a wire share a node, the ground node is the reference."
+                    .to_string(),
+            ),
+            result: None,
+            sub_steps: self
+                .nodes()
+                .iter()
+                .map(|x| x.upgrade().unwrap())
+                .map(|x| SubStep {
+                    description: Some(format!("Node {}: {}", x.borrow().id, names(&x.borrow()))),
+                    result: None,
+                    operations: vec![],
+                })
+                .collect(),
+        }];
+
+        let super_nodes: Vec<SubStep> = self
+            .get_tools(SuperNode)
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .map(|x| {
+                let tool = x.borrow();
+                let nodes: Vec<String> = self
+                    .subsumed_nodes(&tool)
+                    .iter()
+                    .map(|x| format!("N{}", x))
+                    .collect();
+                SubStep {
+                    description: Some(format!(
+                        "Supernode {}: {} joins {}",
+                        tool.id,
+                        names(&tool),
+                        nodes.join(", ")
+                    )),
+                    result: None,
+                    operations: vec![],
+                }
+            })
+            .collect();
+        if !super_nodes.is_empty() {
+            steps.push(Step {
+                title: Some("Identify Supernodes".to_string()),
+                description: Some(
+                    "A voltage source between two nodes merges them into a supernode.".to_string(),
+                ),
+                result: None,
+                sub_steps: super_nodes,
+            });
+        }
+        steps
+    }
+
+    /// Id of the supernode formed around a floating voltage source, None for any other element.
+    pub fn supernode_for_source(&self, source_id: usize) -> Option<usize> {
+        if self.get_element_by_id(source_id)?.borrow().class != VoltageSrc {
+            return None;
+        }
+        self.get_tools(SuperNode)
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .find(|x| x.borrow().member_ids().contains(&source_id))
+            .map(|x| x.borrow().id)
+    }
+
+    /// Lowest id of an element that belongs to both tools, ground is not counted.
+    ///
+    /// Works for any pair of tools, e.g. the branch two meshes share or the element between
+    /// two nodes.
+    pub fn shared_element(&self, tool_a: usize, tool_b: usize) -> Option<usize> {
+        let members = |id: usize| -> Option<Vec<usize>> {
+            let tool = self.tools.iter().find(|x| x.borrow().id == id)?;
+            let ids: Vec<usize> = tool.borrow().member_ids();
+            Some(ids)
+        };
+        let (a, b): (Vec<usize>, Vec<usize>) = (members(tool_a)?, members(tool_b)?);
+        a.into_iter()
+            .filter(|x| {
+                b.contains(x) && self.get_element_by_id(*x).unwrap().borrow().class != Ground
+            })
+            .min()
+    }
+
+    /// Create a SuperNode around every floating voltage source.
+    ///
+    /// Each supernode keeps the nodes on its source's positive and negative sides. Sources
+    /// shorted onto one node, or parallel sources that disagree once their orientation is
+    /// taken into account, are rejected here ahead of any solve. Existing supernodes are
+    /// replaced.
     pub fn create_super_nodes(&mut self) -> Result<&mut Self, String> {
+        self.tools.retain(|x| x.borrow().class != SuperNode);
         let mut super_nodes: Vec<Tool> = Vec::new();
         let mut valid_sources: Vec<Weak<RefCell<Element>>> = Vec::new();
         for element in &self.elements {
             match element.borrow().class {
                 VoltageSrc => {
-                    if !element.borrow().connected_to_ground() {
+                    if !element.borrow().connected_to_ground(self.ground) {
                         valid_sources.push(Rc::downgrade(element));
                     }
                 }
@@ -213,21 +701,54 @@ impl Container {
             }
         }
 
+        // (Source, Positive node, Negative node) for every source with both nodes known
+        let mut oriented: Vec<(Element, usize, usize)> = Vec::new();
         for source in valid_sources {
+            let element: Element = source.upgrade().unwrap().borrow().clone();
+            let terminals: Option<(usize, usize)> = self
+                .terminal_node(&element, &element.positive)
+                .zip(self.terminal_node(&element, &element.negative));
+            if let Some((positive, negative)) = terminals {
+                if positive == negative {
+                    return Err(format!(
+                        "{} has both terminals on node {}",
+                        element.basic_string(),
+                        positive
+                    ));
+                }
+                if let Some((other, _, _)) = oriented.iter().find(|(other, a, b)| {
+                    let sign: f64 = match (*a, *b) {
+                        x if x == (positive, negative) => 1.0,
+                        x if x == (negative, positive) => -1.0,
+                        _ => return false,
+                    };
+                    (other.value - sign * element.value).abs() > 1e-9
+                }) {
+                    return Err(format!(
+                        "{} and {} fix nodes {} and {} to different voltages",
+                        other.basic_string(),
+                        element.basic_string(),
+                        positive,
+                        negative
+                    ));
+                }
+                oriented.push((element.clone(), positive, negative));
+            }
+
             let mut members: Vec<Weak<RefCell<Element>>> = Vec::new();
             for element in &source.upgrade().unwrap().borrow().positive {
-                members.push(Rc::downgrade(self.get_element_by_id(*element)));
+                members.push(Rc::downgrade(self.get_element_by_id(*element).unwrap()));
             }
             for element in &source.upgrade().unwrap().borrow().negative {
                 if !members
                     .iter()
                     .any(|x| x.upgrade().unwrap().borrow().id == *element)
                 {
-                    members.push(Rc::downgrade(self.get_element_by_id(*element)));
+                    members.push(Rc::downgrade(self.get_element_by_id(*element).unwrap()));
                 }
             }
             members.push(source);
-            super_nodes.push(Tool::create_supernode(members));
+            super_nodes.push(Tool::create_supernode(members, terminals));
         }
 
         for node in super_nodes {
@@ -237,18 +758,29 @@ impl Container {
         Ok(self)
     }
 
+    /// Create a Mesh for every cycle in the basis of the node graph.
+    ///
+    /// The cycle basis comes back in no particular order, so members and meshes are sorted
+    /// to keep the mesh numbering the same across runs. Existing meshes are replaced.
     pub fn create_meshes(&mut self) -> &mut Self {
-        let graph: UnGraph<i32, ()> = Tool::nodes_to_graph(&self.nodes()).unwrap();
-        let root = Some(self.ground);
-        let x: Vec<Vec<usize>> = connectivity::cycle_basis(&graph, root.map(NodeIndex::new))
+        self.tools.retain(|x| x.borrow().class != ToolType::Mesh);
+        let graph: UnGraph<i32, ()> = Tool::nodes_to_graph(&self.nodes(), self.ground).unwrap();
+        // Ground is vertex 0 of the node graph whatever its element id
+        let root = Some(0);
+        let mut x: Vec<Vec<usize>> = connectivity::cycle_basis(&graph, root.map(NodeIndex::new))
             .into_iter()
-            .map(|res_map| res_map.into_iter().map(|x| x.index()).collect())
+            .map(|res_map| {
+                let mut mesh: Vec<usize> = res_map.into_iter().map(|x| x.index()).collect();
+                mesh.sort();
+                mesh
+            })
             .collect();
+        x.sort();
 
         for mesh in x {
             self.add_tool(Tool::create_mesh(
                 mesh.iter()
-                    .map(|x| self.get_element_by_id(*x))
+                    .map(|x| self.get_element_by_id(*x).unwrap())
                     .map(|x| Rc::downgrade(x))
                     .collect(),
             ));
@@ -259,6 +791,239 @@ impl Container {
 
     pub fn create_super_meshes(&mut self) {}
 
+    /// Check that every tool member still points at a live element.
+    ///
+    /// Reports the dangling references instead of letting a solver panic on them.
+    pub fn verify_references(&self) -> Result<(), StatusError> {
+        let dangling: Vec<String> = self
+            .tools
+            .iter()
+            .flat_map(|tool| {
+                let tool = tool.borrow();
+                tool.members
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| x.upgrade().is_none())
+                    .map(|(i, _)| format!("tool {} member {}", tool.id, i))
+                    .collect::<Vec<String>>()
+            })
+            .collect();
+        match dangling.is_empty() {
+            true => Ok(()),
+            false => Err(Known(format!(
+                "Dangling references: {}",
+                dangling.join(", ")
+            ))),
+        }
+    }
+
+    /// Number of branches that can carry current, every enabled element except ground.
+    pub fn branch_count(&self) -> usize {
+        self.elements
+            .iter()
+            .filter(|x| x.borrow().class != Ground && x.borrow().enabled)
+            .count()
+    }
+
+    /// Check that there is a mesh for every independent loop, branches - nodes + 1.
+    ///
+    /// Ground counts as a node. Call after `create_meshes`.
+    pub fn verify_mesh_basis(&self) -> Result<(), StatusError> {
+        let branches: usize = self.branch_count();
+        let nodes: usize = self.nodes().len() + 1;
+        let expected: usize = (branches + 1).saturating_sub(nodes);
+        let meshes: usize = self.get_tools(ToolType::Mesh).len();
+        match meshes == expected {
+            true => Ok(()),
+            false => Err(Known(format!(
+                "Found {} meshes but the circuit has {} independent loops",
+                meshes, expected
+            ))),
+        }
+    }
+
+    /// Every fundamental cycle as element ids in the order they are walked.
+    ///
+    /// A spanning tree is grown out from ground, each branch left out of it closes one
+    /// loop. Call after `create_nodes`.
+    pub fn all_cycles(&self) -> Vec<Vec<usize>> {
+        let pairs: Vec<(usize, usize, Rc<RefCell<Element>>)> = self.get_all_node_pairs();
+        let tree: SpanningTree = Container::spanning_tree(&pairs);
+        let id = |k: usize| -> usize { pairs[k].2.borrow().id };
+
+        let path_to_root = |mut node: usize| -> Vec<(usize, usize)> {
+            let mut path: Vec<(usize, usize)> = Vec::new(); // (Node, Element ID towards root)
+            while let Some((k, up)) = tree.parent[node] {
+                path.push((node, id(k)));
+                node = up;
+            }
+            path.push((node, usize::MAX));
+            path
+        };
+
+        pairs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !tree.in_tree[*i])
+            .map(|(i, (a, b, _))| {
+                let (from_a, from_b) = (path_to_root(*a), path_to_root(*b));
+                let common: usize = from_a
+                    .iter()
+                    .find(|(node, _)| from_b.iter().any(|x| x.0 == *node))
+                    .map_or(0, |x| x.0);
+
+                // Across the closing branch from a to b, up to the common node and back down to a
+                let mut cycle: Vec<usize> = vec![id(i)];
+                cycle.extend(
+                    from_b
+                        .iter()
+                        .take_while(|(node, _)| *node != common)
+                        .map(|x| x.1),
+                );
+                let down: Vec<usize> = from_a
+                    .iter()
+                    .take_while(|(node, _)| *node != common)
+                    .map(|x| x.1)
+                    .collect();
+                cycle.extend(down.iter().rev());
+                cycle
+            })
+            .collect()
+    }
+
+    /// Enable or disable an element, solvers leave disabled elements open.
+    ///
+    /// Tools are cleared and have to be created again.
+    pub fn set_enabled(&mut self, id: usize, enabled: bool) -> Result<&mut Self, StatusError> {
+        match self.get_element_by_id(id) {
+            Some(element) => element.borrow_mut().enabled = enabled,
+            None => return Err(Known(format!("Element {} does not exist", id))),
+        }
+        self.tools.clear();
+        Ok(self)
+    }
+
+    /// Designate an existing node as the 0V reference for solving.
+    ///
+    /// The Ground element still anchors the topology, solvers shift the node voltages
+    /// so the chosen node reads 0V and the ground node sits at the negated offset.
+    pub fn set_reference_node(&mut self, node_id: usize) -> Result<&mut Self, StatusError> {
+        if !self
+            .nodes()
+            .iter()
+            .any(|x| x.upgrade().unwrap().borrow().id == node_id)
+        {
+            return Err(Known(format!("Node {} does not exist", node_id)));
+        }
+        self.reference = Some(node_id);
+        Ok(self)
+    }
+
+    /// The node used as the 0V reference, None when ground is the reference.
+    pub fn reference_node(&self) -> Option<usize> {
+        self.reference
+    }
+
+    /// Adjust resistor values for an operating temperature in °C.
+    ///
+    /// Resistors with a temperature coefficient take `R0·(1 + α·(T - T0))`, where R0 is
+    /// the value given at [crate::elements::REFERENCE_TEMPERATURE], so repeated calls do not compound.
+    pub fn apply_temperature(&mut self, temp_c: f64) -> &mut Self {
+        for element in self.elements.iter() {
+            let mut element = element.borrow_mut();
+            if element.class == Resistor {
+                element.apply_temperature(temp_c);
+            }
+        }
+        self
+    }
+
+    /// Current through an element from the last solve, None until the circuit is solved.
+    ///
+    /// Only resistor and current source currents are written back by the solvers.
+    pub fn current_through(&self, element_id: usize) -> Option<f64> {
+        let element = self.get_element_by_id(element_id)?.borrow();
+        if !self.is_solved() {
+            return None;
+        }
+        match element.class {
+            Resistor | CurrentSrc => Some(element.current.value()),
+            _ => None,
+        }
+    }
+
+    /// Potential difference across an element from the solved node voltages.
+    ///
+    /// Measured from the first to the second node of the element's node pair, the direction
+    /// `current_through` uses, so a resistor reads I·R and a voltage source its value.
+    pub fn voltage_across(&self, element_id: usize) -> Option<f64> {
+        if !self.is_solved() {
+            return None;
+        }
+        let (node1, node2, _) = self
+            .get_all_node_pairs()
+            .into_iter()
+            .find(|x| x.2.borrow().id == element_id)?;
+        let voltage = |node: usize| match self.node_index(node) {
+            Some(index) => self.get_tool_by_id(index).borrow().value,
+            None => self.ground_voltage,
+        };
+        Some(voltage(node1) - voltage(node2))
+    }
+
+    /// Potential of the ground node, nonzero when another node is the reference.
+    pub(crate) fn set_ground_voltage(&mut self, voltage: f64) {
+        self.ground_voltage = voltage;
+    }
+
+    /// Solvers write the node voltages back, nodes start out as NaN.
+    fn is_solved(&self) -> bool {
+        let nodes: Vec<Weak<RefCell<Tool>>> = self.nodes();
+        !nodes.is_empty()
+            && nodes
+                .iter()
+                .all(|x| !x.upgrade().unwrap().borrow().value.is_nan())
+    }
+
+    /// Where the container is in its life, derived from its current contents.
+    ///
+    /// New until it validates, Valid once it does and Solved once a solver has written
+    /// a value to every node. Recreating the nodes goes back to Valid.
+    pub fn status(&self) -> Status {
+        if self.validate().is_err() {
+            return Status::New;
+        }
+        match self.is_solved() {
+            true => Status::Solved,
+            false => Status::Valid,
+        }
+    }
+
+    /// Check that solving gave every node a finite value.
+    ///
+    /// Supernodes have no potential of their own, the nodes they join are checked instead.
+    pub fn verify_solved(&self) -> Result<(), StatusError> {
+        let nodes: Vec<Weak<RefCell<Tool>>> = self.nodes();
+        if nodes.is_empty() {
+            return Err(Known(
+                "No nodes to verify, call create_nodes first".to_string(),
+            ));
+        }
+        let unsolved: Vec<String> = nodes
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .filter(|x| !x.borrow().value.is_finite())
+            .map(|x| x.borrow().id.to_string())
+            .collect();
+        match unsolved.is_empty() {
+            true => Ok(()),
+            false => Err(Known(format!(
+                "Nodes without a finite value: {}",
+                unsolved.join(", ")
+            ))),
+        }
+    }
+
     pub fn get_elements(&self) -> &Vec<Rc<RefCell<Element>>> {
         &self.elements
     }
@@ -286,47 +1051,1411 @@ impl Container {
             .collect()
     }
 
-    /// Get all the node pairs in the circuit.
-    ///
-    /// Returns a vector of tuples containing the node ids and the element
-    pub fn get_all_node_pairs(&self) -> Vec<(usize, usize, Rc<RefCell<Element>>)> {
-        let mut node_to_node_resistors: Vec<(usize, usize, Rc<RefCell<Element>>)> = Vec::new();
+    /// Node on one side of an element, 0 when that side is ground. Call after `create_nodes`.
+    pub(crate) fn terminal_node(&self, element: &Element, side: &[usize]) -> Option<usize> {
+        if side.iter().any(|x| {
+            self.get_element_by_id(*x)
+                .map_or(false, |x| x.borrow().class == Ground)
+        }) {
+            return Some(0);
+        }
+        self.nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .find(|x| {
+                let members: Vec<usize> = x.borrow().member_ids();
+                members.contains(&element.id) && side.iter().all(|y| members.contains(y))
+            })
+            .map(|x| x.borrow().id)
+    }
 
-        for element in self.elements.iter() {
-            if node_to_node_resistors
-                .iter()
+    /// Node voltage from Millman's theorem, `Σ(E/R) / Σ(1/R)` over the branches at the node.
+    ///
+    /// Every branch has to be a resistor to ground, either directly or through a voltage
+    /// source on a node of their own. Call after `create_nodes`.
+    pub fn millman_voltage(&self, node_id: usize) -> Result<f64, StatusError> {
+        if self.node_index(node_id).is_none() {
+            return Err(Known(format!("Node {} does not exist", node_id)));
+        }
+        let sides = |element: &Element| {
+            (
+                self.terminal_node(element, &element.positive),
+                self.terminal_node(element, &element.negative),
+            )
+        };
+        let not_a_branch = |id: usize| Known(format!("Element {} is not a Millman branch", id));
+
+        let (mut currents, mut conductances): (f64, f64) = (0.0, 0.0);
+        for element in self.elements.iter().map(|x| x.borrow().clone()) {
+            let far: usize = match sides(&element) {
+                (Some(a), Some(b)) if a == node_id => b,
+                (Some(a), Some(b)) if b == node_id => a,
+                _ => continue,
+            };
+            if element.class != Resistor {
+                return Err(not_a_branch(element.id));
+            }
+
+            let mut source_voltage: f64 = 0.0;
+            if let Some(index) = self.node_index(far) {
+                let members: Vec<usize> = self.get_tool_by_id(index).borrow().member_ids();
+                let source: Element = members
+                    .iter()
+                    .map(|x| self.get_element_by_id(*x).unwrap().borrow().clone())
+                    .find(|x| x.id != element.id)
+                    .filter(|x| x.class == VoltageSrc && members.len() == 2)
+                    .ok_or_else(|| not_a_branch(element.id))?;
+                source_voltage = match sides(&source) {
+                    (Some(a), Some(0)) if a == far => source.value,
+                    (Some(0), Some(b)) if b == far => -source.value,
+                    _ => return Err(not_a_branch(source.id)),
+                };
+            }
+            currents += source_voltage * element.conductance();
+            conductances += element.conductance();
+        }
+
+        match conductances > 0.0 {
+            true => Ok(currents / conductances),
+            false => Err(Known(format!("Node {} has no branches", node_id))),
+        }
+    }
+
+    /// Get all the node pairs in the circuit.
+    ///
+    /// Returns a vector of tuples containing the node ids and the element.
+    /// Elements without a node are left out, so this is empty before `create_nodes`.
+    pub fn get_all_node_pairs(&self) -> Vec<(usize, usize, Rc<RefCell<Element>>)> {
+        let mut node_to_node_resistors: Vec<(usize, usize, Rc<RefCell<Element>>)> = Vec::new();
+
+        for element in self.elements.iter() {
+            if node_to_node_resistors
+                .iter()
                 .any(|x| x.2.borrow().id == element.borrow().id)
                 || element.borrow().class == Ground
             {
                 continue;
             }
 
+            // Elements with both sides on ground have no node, validate reports them
             let tools = self.get_tools_for_element(element.borrow().id);
-            if element.borrow().connected_to_ground() {
-                node_to_node_resistors.push((
-                    tools[0].upgrade().unwrap().borrow().id,
-                    0,
-                    element.clone(),
-                ));
-            } else {
-                node_to_node_resistors.push((
-                    tools[0].upgrade().unwrap().borrow().id,
-                    tools[1].upgrade().unwrap().borrow().id,
-                    element.clone(),
-                ));
+            if tools.is_empty() {
+                continue;
             }
+            let second: usize = match (
+                element.borrow().connected_to_ground(self.ground),
+                tools.get(1),
+            ) {
+                (true, _) => 0,
+                (false, Some(tool)) => tool.upgrade().unwrap().borrow().id,
+                (false, None) => continue,
+            };
+            node_to_node_resistors.push((
+                tools[0].upgrade().unwrap().borrow().id,
+                second,
+                element.clone(),
+            ));
         }
 
         node_to_node_resistors
     }
 
+    /// Whether the node graph can be drawn without crossing branches.
+    ///
+    /// Mesh analysis only applies to planar circuits. Call after `create_nodes`.
+    pub fn is_planar(&self) -> bool {
+        let edges: Vec<(usize, usize)> = self
+            .get_all_node_pairs()
+            .iter()
+            .map(|(a, b, _)| (*a, *b))
+            .collect();
+        let vertices: usize = edges.iter().map(|(a, b)| *a.max(b)).max().unwrap_or(0) + 1;
+        planarity::is_planar(vertices, &edges)
+    }
+
+    /// Rank of the incidence matrix with the ground row removed.
+    ///
+    /// Equals the number of nodes for a circuit connected to ground, every island that does
+    /// not reach ground lowers it by one. Call after `create_nodes`.
+    pub fn topology_rank(&self) -> usize {
+        let nodes: Vec<usize> = self
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect();
+        let pairs: Vec<(usize, usize, Rc<RefCell<Element>>)> = self.get_all_node_pairs();
+        if nodes.is_empty() || pairs.is_empty() {
+            return 0;
+        }
+
+        let mut incidence: DMatrix<f64> = DMatrix::zeros(nodes.len(), pairs.len());
+        for (k, (a, b, _)) in pairs.iter().enumerate() {
+            if let Some(row) = nodes.iter().position(|x| x == a) {
+                incidence[(row, k)] += 1.0;
+            }
+            if let Some(row) = nodes.iter().position(|x| x == b) {
+                incidence[(row, k)] -= 1.0;
+            }
+        }
+        incidence.rank(1e-9)
+    }
+
+    /// Numeric node conductance matrix, the G block of the MNA system.
+    ///
+    /// Rows and columns follow the node ids, unlike the node matrix solver which lays the
+    /// nodes out in reverse. Only resistors contribute. Call after `create_nodes`.
+    pub fn conductance_matrix(&self) -> DMatrix<f64> {
+        let size: usize = self.nodes().len();
+        let mut g: DMatrix<f64> = DMatrix::zeros(size, size);
+        for (a, b, element) in self.get_all_node_pairs() {
+            let conductance: f64 = element.borrow().conductance();
+            let a: Option<usize> = self.node_index(a);
+            let b: Option<usize> = self.node_index(b);
+            for i in [a, b].into_iter().flatten() {
+                g[(i, i)] += conductance;
+            }
+            if let (Some(i), Some(j)) = (a, b) {
+                g[(i, j)] -= conductance;
+                g[(j, i)] -= conductance;
+            }
+        }
+        g
+    }
+
+    /// Recognize a voltage divider, Wheatstone bridge or resistor ladder from the node graph.
+    ///
+    /// Only circuits of resistors fed by a single voltage source are classified.
+    /// Call after `create_nodes`.
+    pub fn classify_topology(&self) -> TopologyHint {
+        let pairs: Vec<(usize, usize, Rc<RefCell<Element>>)> = self.get_all_node_pairs();
+        let sources: Vec<(usize, usize)> = pairs
+            .iter()
+            .filter(|(_, _, x)| x.borrow().class == VoltageSrc)
+            .map(|(a, b, _)| (*a, *b))
+            .collect();
+        let resistors: Vec<(usize, usize)> = pairs
+            .iter()
+            .filter(|(_, _, x)| x.borrow().class == Resistor)
+            .map(|(a, b, _)| (*a.min(b), *a.max(b)))
+            .collect();
+        if sources.len() != 1 || resistors.len() + 1 != pairs.len() || resistors.len() < 2 {
+            return TopologyHint::Unrecognized;
+        }
+        let nodes: usize = self.nodes().len();
+        let source: (usize, usize) = (
+            sources[0].0.min(sources[0].1),
+            sources[0].0.max(sources[0].1),
+        );
+
+        // A single loop visits every vertex, ground included, through exactly two elements
+        let degree = |node: usize| {
+            pairs
+                .iter()
+                .filter(|(a, b, _)| *a == node || *b == node)
+                .count()
+        };
+        if pairs.len() == nodes + 1
+            && self.topology_rank() == nodes
+            && (0..=nodes).all(|x| degree(x) == 2)
+        {
+            return TopologyHint::VoltageDivider;
+        }
+
+        // Every pair of the four vertices, ground included, is joined exactly once
+        let mut edges: Vec<(usize, usize)> = resistors.clone();
+        edges.push(source);
+        edges.sort();
+        edges.dedup();
+        if nodes == 3 && edges.len() == 6 && pairs.len() == 6 && edges.iter().all(|(a, b)| a != b) {
+            return TopologyHint::WheatstoneBridge;
+        }
+
+        // Walk the series resistors away from the grounded source, shunting every node after it
+        if source.0 == 0 {
+            let (mut previous, mut current): (usize, usize) = (0, source.1);
+            let mut visited: usize = 1;
+            loop {
+                let next: Vec<usize> = resistors
+                    .iter()
+                    .filter(|(a, b)| *a != 0 && (*a == current || *b == current))
+                    .map(|(a, b)| if *a == current { *b } else { *a })
+                    .filter(|x| *x != previous)
+                    .collect();
+                match next.as_slice() {
+                    [] => break,
+                    [x] => {
+                        (previous, current) = (current, *x);
+                        visited += 1;
+                    }
+                    _ => return TopologyHint::Unrecognized,
+                }
+                if visited > nodes {
+                    return TopologyHint::Unrecognized;
+                }
+            }
+            let shunts = |node: usize| resistors.iter().filter(|x| **x == (0, node)).count();
+            let series: usize = resistors.iter().filter(|(a, _)| *a != 0).count();
+            if visited == nodes
+                && nodes > 2
+                && series == nodes - 1
+                && shunts(source.1) == 0
+                && (1..=nodes)
+                    .filter(|x| *x != source.1)
+                    .all(|x| shunts(x) == 1)
+            {
+                return TopologyHint::ResistorLadder;
+            }
+        }
+        TopologyHint::Unrecognized
+    }
+
+    /// Independent loops of the circuit as (element id, direction) lists, one per mesh.
+    ///
+    /// Built from a spanning tree of the node graph rooted at ground, every element left
+    /// out of the tree closes one loop. A direction of 1 walks the element's node pair from
+    /// the first node to the second, see `get_all_node_pairs`.
+    pub fn fundamental_loops(&self) -> Result<Vec<Vec<(usize, f64)>>, StatusError> {
+        let pairs: Vec<(usize, usize, Rc<RefCell<Element>>)> = self.get_all_node_pairs();
+        let SpanningTree {
+            parent,
+            visited,
+            in_tree,
+        } = Container::spanning_tree(&pairs);
+        if pairs.iter().any(|(a, b, _)| !visited[*a] || !visited[*b]) {
+            return Err(Known("Circuit is not connected to ground".to_string()));
+        }
+
+        let path_to_ground = |mut node: usize| -> Vec<(usize, f64)> {
+            let mut path: Vec<(usize, f64)> = Vec::new();
+            while let Some((k, next)) = parent[node] {
+                path.push((k, if pairs[k].0 == node { 1.0 } else { -1.0 }));
+                node = next;
+            }
+            path
+        };
+
+        let mut loops: Vec<Vec<(usize, f64)>> = Vec::new();
+        for (k, (a, b, _)) in pairs.iter().enumerate() {
+            if in_tree[k] || a == b {
+                continue;
+            }
+            // Across the element, back down to ground from its second node, then up to its first
+            let mut terms: Vec<(usize, f64)> = vec![(k, 1.0)];
+            let tree_path = path_to_ground(*b)
+                .into_iter()
+                .chain(path_to_ground(*a).into_iter().map(|(j, d)| (j, -d)));
+            for (j, d) in tree_path {
+                match terms.iter_mut().find(|x| x.0 == j) {
+                    Some(term) => term.1 += d,
+                    None => terms.push((j, d)),
+                }
+            }
+            loops.push(
+                terms
+                    .into_iter()
+                    .filter(|(_, d)| *d != 0.0)
+                    .map(|(j, d)| (pairs[j].2.borrow().id, d))
+                    .collect(),
+            );
+        }
+
+        Ok(loops)
+    }
+
+    /// Spanning tree of the node pairs grown breadth first out from ground.
+    fn spanning_tree(pairs: &[(usize, usize, Rc<RefCell<Element>>)]) -> SpanningTree {
+        let vertices: usize = pairs.iter().map(|(a, b, _)| *a.max(b)).max().unwrap_or(0) + 1;
+        let mut parent: Vec<Option<(usize, usize)>> = vec![None; vertices];
+        let mut visited: Vec<bool> = vec![false; vertices];
+        let mut in_tree: Vec<bool> = vec![false; pairs.len()];
+        let mut queue: VecDeque<usize> = VecDeque::from(vec![0]);
+        visited[0] = true;
+        while let Some(node) = queue.pop_front() {
+            for (k, (a, b, _)) in pairs.iter().enumerate() {
+                let other: usize = match (*a == node, *b == node) {
+                    (true, false) => *b,
+                    (false, true) => *a,
+                    _ => continue,
+                };
+                if !visited[other] {
+                    visited[other] = true;
+                    in_tree[k] = true;
+                    parent[other] = Some((k, node));
+                    queue.push_back(other);
+                }
+            }
+        }
+        SpanningTree {
+            parent,
+            visited,
+            in_tree,
+        }
+    }
+
+    /// Validate, with `ValidationMode::Strict` also turning the lints into errors.
+    ///
+    /// Strict rejects elements shorted to themselves, `dead_branches` and resistances
+    /// outside `TYPICAL_RESISTANCE`. Call after `create_nodes` for the node based checks.
+    pub fn validate_with_mode(&self, mode: ValidationMode) -> ValidationResult {
+        let lenient: ValidationResult = self.validate();
+        if mode == ValidationMode::Lenient {
+            return lenient;
+        }
+
+        let mut errors: Vec<StatusError> = match lenient {
+            Ok(_) => vec![],
+            Err(StatusError::Multiple(errors)) => errors,
+            Err(error) => vec![error],
+        };
+        for element in self.elements.iter().map(|x| x.borrow()) {
+            if element.class == Ground {
+                continue;
+            }
+            if let Some(id) = element
+                .positive
+                .iter()
+                .find(|x| element.negative.contains(x))
+            {
+                errors.push(Known(format!(
+                    "Element {} cannot be shorted to id {}",
+                    element.id, id
+                )));
+            }
+            let (low, high): (f64, f64) = TYPICAL_RESISTANCE;
+            if element.class == Resistor && (element.value < low || element.value > high) {
+                errors.push(Known(format!(
+                    "Element {} value {} is outside the typical range of {:e} to {:e}",
+                    element.id, element.value, low, high
+                )));
+            }
+        }
+        for id in self.dead_branches() {
+            errors.push(Known(format!("Element {} is on a dead-end branch", id)));
+        }
+
+        match errors.len() {
+            0 => Ok(Status::Valid),
+            1 => Err(errors[0].clone()),
+            _ => Err(StatusError::Multiple(errors)),
+        }
+    }
+
+    /// Elements sitting on a dead-end branch that can not carry any current.
+    ///
+    /// These are elements with a terminal connected to nothing, or that are the only
+    /// member of their node. Call after `create_nodes` to pick up the node based check.
+    pub fn dead_branches(&self) -> Vec<usize> {
+        let mut dead: Vec<usize> = self
+            .elements
+            .iter()
+            .filter(|x| x.borrow().class != Ground)
+            .filter(|x| x.borrow().positive.is_empty() || x.borrow().negative.is_empty())
+            .map(|x| x.borrow().id)
+            .collect();
+        for node in self.nodes() {
+            let member_ids: Vec<usize> = node.upgrade().unwrap().borrow().member_ids();
+            if member_ids.len() == 1 {
+                dead.extend(member_ids);
+            }
+        }
+        dead.sort();
+        dead.dedup();
+        dead
+    }
+
+    /// Load a Container from the JSON produced by `to_json`.
+    ///
+    /// The loaded Container is validated before it is returned.
+    pub fn from_json(json: &str) -> Result<Container, StatusError> {
+        let parsed: ContainerJson = match serde_json::from_str(json) {
+            Ok(parsed) => parsed,
+            Err(e) => return Err(Known(format!("Failed to parse container: {}", e))),
+        };
+
+        let mut container = Container::from_elements(parsed.elements);
+        container.ground = parsed.ground;
+        container.validate()?;
+        Ok(container)
+    }
+
+    /// Load a Container from a KiCad netlist export.
+    ///
+    /// Supports R, V and I components, nets named GND or 0 connect to ground.
+    pub fn from_kicad_netlist(netlist: &str) -> Result<Container, StatusError> {
+        let mut container = Container::new();
+        for element in parse_netlist(netlist)? {
+            container.add_element_core(element);
+        }
+        container.validate()?;
+        Ok(container)
+    }
+
+    /// Serialize the elements and ground index, tools are left out as they are derived.
+    pub fn to_json(&self) -> String {
+        json!({
+            "elements": self.elements,
+            "ground": self.ground,
+        })
+        .to_string()
+    }
+
+    /// Mean and standard deviation of every node voltage with resistors in tolerance.
+    ///
+    /// Each sample scales every resistor by a uniform factor in `1 ± tolerance` and solves
+    /// a copy with the node step solver, samples that fail to solve are left out.
+    pub fn monte_carlo(&self, samples: usize, tolerance: f64) -> MonteCarloResult {
+        let mut random: XorShift = XorShift(MONTE_CARLO_SEED);
+
+        let mut nodes: Vec<usize> = Vec::new();
+        let mut runs: Vec<Vec<f64>> = Vec::new();
+        for _ in 0..samples {
+            let solved = self.solve_modified(|container| {
+                for element in container.elements.iter() {
+                    let mut element = element.borrow_mut();
+                    if element.class == Resistor {
+                        element.value *= 1.0 + tolerance * (2.0 * random.next_f64() - 1.0);
+                    }
+                }
+            });
+            let container: Rc<RefCell<Container>> = match solved {
+                Ok(container) => container,
+                Err(_) => continue,
+            };
+            let solved: Vec<Rc<RefCell<Tool>>> = container
+                .borrow()
+                .nodes()
+                .iter()
+                .map(|x| x.upgrade().unwrap())
+                .collect();
+            nodes = solved.iter().map(|x| x.borrow().id).collect();
+            runs.push(solved.iter().map(|x| x.borrow().value).collect());
+        }
+
+        let n: f64 = runs.len() as f64;
+        let mean: Vec<f64> = (0..nodes.len())
+            .map(|i| runs.iter().map(|x| x[i]).sum::<f64>() / n)
+            .collect();
+        let std_dev: Vec<f64> = (0..nodes.len())
+            .map(|i| match runs.len() > 1 {
+                true => {
+                    let squares: f64 = runs.iter().map(|x| (x[i] - mean[i]).powi(2)).sum();
+                    (squares / (n - 1.0)).sqrt()
+                }
+                false => 0.0,
+            })
+            .collect();
+
+        MonteCarloResult {
+            samples: runs.len(),
+            nodes,
+            mean,
+            std_dev,
+        }
+    }
+
+    /// Series chains and parallel groups of resistors, reported without changing anything.
+    ///
+    /// Resistors are in series when the node between them holds nothing else, and in
+    /// parallel when both of their nodes are the same.
+    pub fn simplification_opportunities(&self) -> Vec<SimplificationOpportunity> {
+        let resistors: Vec<Element> = self
+            .elements
+            .iter()
+            .map(|x| x.borrow().clone())
+            .filter(|x| x.class == Resistor && x.enabled)
+            .collect();
+        let value = |id: usize| self.get_element_by_id(id).unwrap().borrow().value;
+
+        // Chains grow out of every pair sharing a node of their own
+        let mut chains: Vec<Vec<usize>> = Vec::new();
+        for element in resistors.iter() {
+            for side in [&element.positive, &element.negative] {
+                let other: usize = match side.as_slice() {
+                    [other] if *other > element.id => *other,
+                    _ => continue,
+                };
+                let other = self.get_element_by_id(other).unwrap().borrow();
+                let back: &Vec<usize> = match other.positive.contains(&element.id) {
+                    true => &other.positive,
+                    false => &other.negative,
+                };
+                if other.class != Resistor || !other.enabled || back != &vec![element.id] {
+                    continue;
+                }
+                let touching: Vec<usize> = (0..chains.len())
+                    .filter(|i| chains[*i].contains(&element.id) || chains[*i].contains(&other.id))
+                    .collect();
+                let mut chain: Vec<usize> = vec![element.id, other.id];
+                for i in touching.iter().rev() {
+                    chain.extend(chains.remove(*i));
+                }
+                chain.sort();
+                chain.dedup();
+                chains.push(chain);
+            }
+        }
+        chains.sort();
+
+        // Nodes are keyed by their sorted members, None for ground
+        let node = |element: &Element, side: &Vec<usize>| -> Option<Vec<usize>> {
+            if side.contains(&self.ground) {
+                return None;
+            }
+            let mut members: Vec<usize> = side.clone();
+            members.push(element.id);
+            members.sort();
+            Some(members)
+        };
+        let mut groups: Vec<(Vec<Option<Vec<usize>>>, Vec<usize>)> = Vec::new();
+        for element in resistors.iter() {
+            let mut key = vec![
+                node(element, &element.positive),
+                node(element, &element.negative),
+            ];
+            key.sort();
+            match groups.iter_mut().find(|x| x.0 == key) {
+                Some(group) => group.1.push(element.id),
+                None => groups.push((key, vec![element.id])),
+            }
+        }
+
+        let series = chains.into_iter().map(|ids| SimplificationOpportunity {
+            kind: Simplification::Series,
+            equivalent: ids.iter().map(|x| value(*x)).sum(),
+            element_ids: ids,
+        });
+        let parallel = groups
+            .into_iter()
+            .filter(|x| x.1.len() > 1)
+            .map(|(_, ids)| SimplificationOpportunity {
+                kind: Simplification::Parallel,
+                equivalent: 1.0 / ids.iter().map(|x| 1.0 / value(*x)).sum::<f64>(),
+                element_ids: ids,
+            });
+        series.chain(parallel).collect()
+    }
+
+    /// Apply series and parallel resistor reductions until none are left.
+    ///
+    /// Returns the applied steps in order. Merged away resistors leave gaps in the ids,
+    /// tools are cleared and have to be created again.
+    pub fn simplify(&mut self) -> Vec<SimplificationStep> {
+        self.reduce_resistors(None)
+    }
+
+    /// Apply the resistor reductions of one kind, or of every kind for None, until none
+    /// are left. See `simplify`.
+    pub(crate) fn reduce_resistors(
+        &mut self,
+        kind: Option<&Simplification>,
+    ) -> Vec<SimplificationStep> {
+        let mut steps: Vec<SimplificationStep> = Vec::new();
+        while let Some(step) = self
+            .simplification_opportunities()
+            .into_iter()
+            .filter(|x| kind.map_or(true, |kind| x.kind == *kind))
+            .find_map(|x| self.apply_opportunity(x))
+        {
+            steps.push(step);
+        }
+        steps
+    }
+
+    /// Replace the resistors of an opportunity by the lowest id among them.
+    ///
+    /// Series chains that do not have exactly two outer ends are left alone.
+    fn apply_opportunity(
+        &mut self,
+        opportunity: SimplificationOpportunity,
+    ) -> Option<SimplificationStep> {
+        let ids: &Vec<usize> = &opportunity.element_ids;
+        let kept: usize = *ids.iter().min()?;
+        if opportunity.kind == Simplification::Series {
+            let mut ends: Vec<(usize, Vec<usize>)> = Vec::new();
+            for id in ids.iter() {
+                let element: Element = self.get_element_by_id(*id).unwrap().borrow().clone();
+                for side in [element.positive, element.negative] {
+                    if !matches!(side.as_slice(), [other] if ids.contains(other)) {
+                        ends.push((*id, side));
+                    }
+                }
+            }
+            if ends.len() != 2 {
+                return None;
+            }
+
+            // Elements on the outer nodes now lead to the kept resistor
+            for (owner, side) in ends.iter() {
+                for id in side.iter() {
+                    let mut element = self.get_element_by_id(*id).unwrap().borrow_mut();
+                    let element: &mut Element = &mut element;
+                    for list in [&mut element.positive, &mut element.negative] {
+                        list.iter_mut()
+                            .filter(|x| **x == *owner)
+                            .for_each(|x| *x = kept);
+                    }
+                }
+            }
+            let mut element = self.get_element_by_id(kept).unwrap().borrow_mut();
+            element.positive = ends[0].1.clone();
+            element.negative = ends[1].1.clone();
+        }
+
+        self.get_element_by_id(kept).unwrap().borrow_mut().value = opportunity.equivalent;
+        for id in ids.iter().filter(|x| **x != kept) {
+            self.remove_element(*id);
+        }
+        Some(SimplificationStep {
+            kind: opportunity.kind,
+            element_ids: opportunity.element_ids,
+            kept,
+            equivalent: opportunity.equivalent,
+        })
+    }
+
+    /// Value a source needs for a node to read a target voltage.
+    ///
+    /// The circuit is linear, so the node voltage is `a·value + b` and two solves on copies
+    /// with different source values pin down a and b.
+    pub fn solve_for_source(
+        &self,
+        source_id: usize,
+        target_node: usize,
+        target_voltage: f64,
+    ) -> Result<f64, StatusError> {
+        let value: f64 = match self.get_element_by_id(source_id) {
+            Some(element) if element.borrow().class.is_source() => element.borrow().value,
+            _ => return Err(Known(format!("Element {} is not a source", source_id))),
+        };
+        let solve = |value: f64| -> Result<f64, StatusError> {
+            let container: Rc<RefCell<Container>> = self.solve_modified(|container| {
+                container
+                    .get_element_by_id(source_id)
+                    .unwrap()
+                    .borrow_mut()
+                    .value = value;
+            })?;
+            let container = container.borrow();
+            let index: usize = match container.node_index(target_node) {
+                Some(index) => index,
+                None => return Err(Known(format!("Node {} does not exist", target_node))),
+            };
+            let voltage: f64 = container.get_tool_by_id(index).borrow().value;
+            Ok(voltage)
+        };
+
+        let (low, high): (f64, f64) = (value, value + value.abs().max(1.0));
+        let (v_low, v_high): (f64, f64) = (solve(low)?, solve(high)?);
+        let slope: f64 = (v_high - v_low) / (high - low);
+        if slope.abs() < 1e-12 {
+            return Err(Known(format!(
+                "Node {} does not depend on source {}",
+                target_node, source_id
+            )));
+        }
+        Ok(low + (target_voltage - v_low) / slope)
+    }
+
+    /// Thevenin equivalent of the circuit seen by a load resistor, along with the load's own
+    /// voltage and current.
+    ///
+    /// The load stays attached, solving again with its value doubled gives the two points
+    /// `V = Vth·R / (Rth + R)` needs. Voltages follow the load's node pair.
+    pub fn port_analysis(&self, load_element_id: usize) -> Result<PortResult, StatusError> {
+        let load: f64 = match self.get_element_by_id(load_element_id) {
+            Some(element) if element.borrow().class == Resistor => element.borrow().value,
+            _ => {
+                return Err(Known(format!(
+                    "Element {} is not a resistor",
+                    load_element_id
+                )))
+            }
+        };
+        let solve = |value: f64| -> Result<(f64, f64), StatusError> {
+            let container: Rc<RefCell<Container>> = self.solve_modified(|container| {
+                container
+                    .get_element_by_id(load_element_id)
+                    .unwrap()
+                    .borrow_mut()
+                    .value = value;
+            })?;
+            let container = container.borrow();
+            match (
+                container.voltage_across(load_element_id),
+                container.current_through(load_element_id),
+            ) {
+                (Some(voltage), Some(current)) => Ok((voltage, current)),
+                _ => Err(Known(format!(
+                    "Element {} is not between two nodes",
+                    load_element_id
+                ))),
+            }
+        };
+
+        let (load_voltage, load_current): (f64, f64) = solve(load)?;
+        let (doubled, _): (f64, f64) = solve(2.0 * load)?;
+        let denominator: f64 = doubled - 2.0 * load_voltage;
+        if denominator.abs() < 1e-12 {
+            return Err(Known(format!(
+                "Element {} is fed by an ideal current source, it has no Thevenin equivalent",
+                load_element_id
+            )));
+        }
+        let thevenin_resistance: f64 = 2.0 * load * (load_voltage - doubled) / denominator;
+        let thevenin_voltage: f64 = load_voltage * (thevenin_resistance + load) / load;
+        Ok(PortResult {
+            thevenin_voltage,
+            thevenin_resistance,
+            norton_current: thevenin_voltage / thevenin_resistance,
+            load_voltage,
+            load_current,
+        })
+    }
+
+    /// Deep copy with every element id and connection shifted up by an offset, without tools.
+    ///
+    /// Meant for embedding a subcircuit, the shifted ids stay clear of the first `offset` ids
+    /// of another container.
+    pub fn clone_with_id_offset(&self, offset: usize) -> Container {
+        let mut copy: Container = Container::new();
+        copy.elements = self
+            .elements
+            .iter()
+            .map(|x| {
+                let mut element: Element = x.borrow().clone();
+                element.id += offset;
+                element.positive.iter_mut().for_each(|x| *x += offset);
+                element.negative.iter_mut().for_each(|x| *x += offset);
+                Rc::new(RefCell::new(element))
+            })
+            .collect();
+        copy.reindex();
+        copy.simplifications = self.simplifications.clone();
+        copy.ground = self.ground + offset;
+        copy.reference = None;
+        copy.ground_voltage = self.ground_voltage;
+        copy.naming = self.naming;
+        copy
+    }
+
+    /// Append the elements of another container and join it at pairs of terminals.
+    ///
+    /// Each pair `(a, b)` joins the node on the positive side of element `a` with the node
+    /// on the positive side of element `b`, using the id `b` has in `other`. The grounds are
+    /// joined as well, `other`'s ground element is dropped. Tools are cleared.
+    pub fn merge(
+        &mut self,
+        other: Container,
+        connections: Vec<(usize, usize)>,
+    ) -> Result<&mut Self, StatusError> {
+        let offset: usize = self.next_id();
+        for (a, b) in connections.iter() {
+            if self.get_element_by_id(*a).is_none() {
+                return Err(Known(format!("Element {} does not exist", a)));
+            }
+            if other.get_element_by_id(*b).is_none() {
+                return Err(Known(format!(
+                    "Element {} does not exist in the merged container",
+                    b
+                )));
+            }
+        }
+
+        let copy: Container = other.clone_with_id_offset(offset);
+        let other_ground: usize = copy.ground;
+        self.elements.extend(copy.elements);
+        self.reindex();
+
+        // Everything on the other ground moves over to ours
+        let mut grounded: Vec<usize> = Vec::new();
+        for element in self.elements.iter() {
+            let mut element = element.borrow_mut();
+            let element: &mut Element = &mut element;
+            for side in [&mut element.positive, &mut element.negative] {
+                if let Some(x) = side.iter_mut().find(|x| **x == other_ground) {
+                    *x = self.ground;
+                    grounded.push(element.id);
+                }
+            }
+        }
+        self.get_element_by_id(self.ground)
+            .unwrap()
+            .borrow_mut()
+            .positive
+            .extend(grounded);
+        self.get_element_by_id(other_ground)
+            .unwrap()
+            .borrow_mut()
+            .positive
+            .clear();
+        self.remove_element(other_ground);
+
+        for (a, b) in connections {
+            let b: usize = match b + offset {
+                x if x == other_ground => self.ground,
+                x => x,
+            };
+            let (first, second) = (self.positive_node_sides(a), self.positive_node_sides(b));
+            for (x, x_positive) in first.iter() {
+                for (y, y_positive) in second.iter().filter(|(y, _)| y != x) {
+                    self.connect_side(*x, *x_positive, *y);
+                    self.connect_side(*y, *y_positive, *x);
+                }
+            }
+        }
+        self.tools.clear();
+        Ok(self)
+    }
+
+    /// (Element ID, on its positive side) for every terminal on the positive side node of an element.
+    fn positive_node_sides(&self, id: usize) -> Vec<(usize, bool)> {
+        let element = self.get_element_by_id(id).unwrap().borrow();
+        let mut sides: Vec<(usize, bool)> = vec![(id, true)];
+        for other in element.positive.iter() {
+            let positive: bool = self
+                .get_element_by_id(*other)
+                .unwrap()
+                .borrow()
+                .positive
+                .contains(&id);
+            sides.push((*other, positive));
+        }
+        sides
+    }
+
+    /// List element `to` on one side of element `from`, if it is not there yet.
+    fn connect_side(&self, from: usize, positive: bool, to: usize) {
+        let mut element = self.get_element_by_id(from).unwrap().borrow_mut();
+        let side: &mut Vec<usize> = match positive {
+            true => &mut element.positive,
+            false => &mut element.negative,
+        };
+        if !side.contains(&to) {
+            side.push(to);
+        }
+    }
+
+    /// Capture the elements and ground for undo, tools are left out as they are derived.
+    pub fn snapshot(&self) -> ContainerSnapshot {
+        ContainerSnapshot {
+            elements: self.elements.iter().map(|x| x.borrow().clone()).collect(),
+            ground: self.ground,
+            reference: self.reference,
+        }
+    }
+
+    /// Return to a snapshot, tools are cleared and have to be created again.
+    pub fn restore(&mut self, snapshot: ContainerSnapshot) -> &mut Self {
+        self.elements = snapshot
+            .elements
+            .into_iter()
+            .map(|x| Rc::new(RefCell::new(x)))
+            .collect();
+        self.reindex();
+        self.tools.clear();
+        self.ground = snapshot.ground;
+        self.reference = snapshot.reference;
+        self.ground_voltage = 0.0;
+        self
+    }
+
+    /// Graphviz DOT representation of the circuit topology.
+    ///
+    /// Graph nodes are the electrical nodes (0 being ground) and every element is an
+    /// edge between the two nodes it connects. Call after `create_nodes`.
+    pub fn to_dot(&self) -> String {
+        let mut lines: Vec<String> = vec!["graph circuit {".to_string()];
+        lines.push("    0 [label=\"GND\"];".to_string());
+        for node in self.nodes() {
+            let node = node.upgrade().unwrap();
+            lines.push(format!(
+                "    {} [label=\"{}\"];",
+                node.borrow().id,
+                node.borrow().pretty_string()
+            ));
+        }
+        for (node1, node2, element) in self.get_all_node_pairs() {
+            lines.push(format!(
+                "    {} -- {} [label=\"{}\"];",
+                node1,
+                node2,
+                element.pretty_string()
+            ));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// Nodes and edges of the topology as JSON for a graph library, the same graph as `to_dot`.
+    ///
+    /// `{"nodes": [{id, label}], "edges": [{from, to, element}]}` where element is the id
+    /// of the element the edge stands for. Call after `create_nodes`.
+    pub fn to_adjacency_json(&self) -> String {
+        let mut nodes: Vec<serde_json::Value> = vec![json!({"id": 0, "label": "GND"})];
+        for node in self.nodes() {
+            let node = node.upgrade().unwrap();
+            nodes.push(json!({
+                "id": node.borrow().id,
+                "label": node.borrow().pretty_string(),
+            }));
+        }
+        let edges: Vec<serde_json::Value> = self
+            .get_all_node_pairs()
+            .iter()
+            .map(|(node1, node2, element)| {
+                json!({"from": node1, "to": node2, "element": element.borrow().id})
+            })
+            .collect();
+        json!({"nodes": nodes, "edges": edges}).to_string()
+    }
+
+    /// Multi-line report of the elements, the tools found so far and the validation status.
+    pub fn describe(&self) -> String {
+        let mut lines: Vec<String> = vec!["Elements:".to_string()];
+        for element in self.elements.iter() {
+            let element = element.borrow();
+            lines.push(format!(
+                "  {} (+{:?} -{:?})",
+                element.pretty_string(),
+                element.positive,
+                element.negative
+            ));
+        }
+        lines.push("Tools:".to_string());
+        for tool in self.tools.iter() {
+            let tool = tool.borrow();
+            lines.push(format!(
+                "  {} members {}",
+                tool.pretty_string(),
+                tool.basic_string()
+            ));
+        }
+        lines.push(match self.validate() {
+            Ok(status) => format!("Status: {}", status),
+            Err(error) => format!("Status: {}", error),
+        });
+        lines.join("\n")
+    }
+
     pub fn get_voltage_sources(&self) -> Vec<Weak<RefCell<Element>>> {
         self.elements
             .iter()
-            .filter(|x| x.borrow().class == VoltageSrc)
+            .filter(|x| x.borrow().class == VoltageSrc && x.borrow().enabled)
             .map(|x| Rc::downgrade(x))
             .collect()
     }
+
+    /// Replace a delta (π) of three resistors with the equivalent wye (T).
+    ///
+    /// The resistors are given in loop order, each one meets the next at a node. The
+    /// elements are reused as the wye branches, branch k sits on the node where delta
+    /// resistors k and k+1 met. Tools are cleared and have to be created again.
+    pub fn delta_to_wye(
+        &mut self,
+        r1: usize,
+        r2: usize,
+        r3: usize,
+    ) -> Result<&mut Self, StatusError> {
+        let ids: [usize; 3] = [r1, r2, r3];
+        let values: Vec<f64> = self.transform_values(&ids)?;
+        let sum: f64 = values.iter().sum();
+
+        let mut outer: Vec<Vec<usize>> = Vec::new();
+        for k in 0..3 {
+            let (a, b) = (ids[k], ids[(k + 1) % 3]);
+            let mut members: Vec<usize> = self.terminal_towards(a, b)?;
+            members.extend(self.terminal_towards(b, a)?);
+            members.retain(|x| !ids.contains(x));
+            members.sort();
+            members.dedup();
+            outer.push(members);
+        }
+
+        self.rewire_members(&outer, |list: &Vec<usize>| {
+            let mut out: Vec<usize> = list.iter().filter(|x| !ids.contains(x)).cloned().collect();
+            for k in 0..3 {
+                if list.contains(&ids[k]) && list.contains(&ids[(k + 1) % 3]) {
+                    out.push(ids[k]);
+                }
+            }
+            out
+        });
+
+        for k in 0..3 {
+            let mut element = self.get_element_by_id(ids[k]).unwrap().borrow_mut();
+            element.value = values[k] * values[(k + 1) % 3] / sum;
+            element.positive = outer[k].clone();
+            element.negative = vec![ids[(k + 1) % 3], ids[(k + 2) % 3]];
+        }
+
+        self.tools.clear();
+        Ok(self)
+    }
+
+    /// Replace a wye (T) of three resistors with the equivalent delta (π).
+    ///
+    /// The resistors must share a common centre node. Delta resistor k is placed between
+    /// the outer nodes of wye branches k and k+1. Tools are cleared and have to be created again.
+    pub fn wye_to_delta(
+        &mut self,
+        r1: usize,
+        r2: usize,
+        r3: usize,
+    ) -> Result<&mut Self, StatusError> {
+        let ids: [usize; 3] = [r1, r2, r3];
+        let values: Vec<f64> = self.transform_values(&ids)?;
+        let product: f64 = values[0] * values[1] + values[1] * values[2] + values[2] * values[0];
+
+        let mut outer: Vec<Vec<usize>> = Vec::new();
+        for k in 0..3 {
+            let element = self.get_element_by_id(ids[k]).unwrap().borrow();
+            let others: [usize; 2] = [ids[(k + 1) % 3], ids[(k + 2) % 3]];
+            let mut members: Vec<usize> = if others.iter().all(|x| element.positive.contains(x)) {
+                element.negative.clone()
+            } else if others.iter().all(|x| element.negative.contains(x)) {
+                element.positive.clone()
+            } else {
+                return Err(Known(format!(
+                    "Resistors {:?} do not share a common node",
+                    ids
+                )));
+            };
+            members.retain(|x| !ids.contains(x));
+            outer.push(members);
+        }
+
+        self.rewire_members(&outer, |list: &Vec<usize>| {
+            let mut out: Vec<usize> = list.iter().filter(|x| !ids.contains(x)).cloned().collect();
+            for k in 0..3 {
+                if list.contains(&ids[k]) {
+                    out.push(ids[k]);
+                    out.push(ids[(k + 2) % 3]);
+                }
+            }
+            out.dedup();
+            out
+        });
+
+        for k in 0..3 {
+            let mut element = self.get_element_by_id(ids[k]).unwrap().borrow_mut();
+            element.value = product / values[(k + 2) % 3];
+            element.positive = [&outer[k][..], &[ids[(k + 2) % 3]][..]].concat();
+            element.negative = [&outer[(k + 1) % 3][..], &[ids[(k + 1) % 3]][..]].concat();
+        }
+
+        self.tools.clear();
+        Ok(self)
+    }
+
+    /// Combine voltage sources in series through a node only they share.
+    ///
+    /// The lower id is kept with the signed sum as its value, flipped if the sum is negative.
+    pub(crate) fn collapse_series_sources(&mut self) -> usize {
+        let mut merged: usize = 0;
+        while let Some((a, b)) = self.series_source_pair() {
+            self.merge_series_sources(a, b);
+            merged += 1;
+        }
+        merged
+    }
+
+    /// Two voltage sources whose shared side connects to nothing else.
+    fn series_source_pair(&self) -> Option<(usize, usize)> {
+        let sources: Vec<Element> = self
+            .elements
+            .iter()
+            .map(|x| x.borrow().clone())
+            .filter(|x| x.class == VoltageSrc)
+            .collect();
+        for a in sources.iter() {
+            for b in sources.iter().filter(|x| x.id > a.id) {
+                if shared_side(a, b.id).is_some() && shared_side(b, a.id).is_some() {
+                    return Some((a.id, b.id));
+                }
+            }
+        }
+        None
+    }
+
+    /// Merge source b into a, a takes over the outer side of b.
+    fn merge_series_sources(&mut self, a: usize, b: usize) {
+        let (a_positive, b_positive, b_outer, b_value) = {
+            let element_a = self.get_element_by_id(a).unwrap().borrow();
+            let element_b = self.get_element_by_id(b).unwrap().borrow();
+            let b_positive: bool = shared_side(&element_b, a).unwrap();
+            let b_outer: Vec<usize> = match b_positive {
+                true => element_b.negative.clone(),
+                false => element_b.positive.clone(),
+            };
+            (
+                shared_side(&element_a, b).unwrap(),
+                b_positive,
+                b_outer,
+                element_b.value,
+            )
+        };
+
+        {
+            let mut element = self.get_element_by_id(a).unwrap().borrow_mut();
+            // Sources meeting with opposite terminals add up
+            match a_positive != b_positive {
+                true => element.value += b_value,
+                false => element.value -= b_value,
+            }
+            match a_positive {
+                true => element.positive = b_outer.clone(),
+                false => element.negative = b_outer.clone(),
+            }
+            if element.value < 0.0 {
+                element.value = -element.value;
+                let positive: Vec<usize> = element.positive.clone();
+                element.positive = element.negative.clone();
+                element.negative = positive;
+            }
+        }
+
+        for id in b_outer {
+            let mut element = self.get_element_by_id(id).unwrap().borrow_mut();
+            let element: &mut Element = &mut element;
+            for side in [&mut element.positive, &mut element.negative] {
+                side.iter_mut().filter(|x| **x == b).for_each(|x| *x = a);
+            }
+        }
+        self.remove_element(b);
+    }
+
+    /// Remove elements with the same class, value and connections as an earlier one.
+    ///
+    /// Duplicates list each other, so those links are left out of the comparison. Returns
+    /// the removed ids, the other elements keep theirs.
+    pub fn deduplicate_elements(&mut self) -> Vec<usize> {
+        let connections = |element: &Element, other: usize| -> (Vec<usize>, Vec<usize>) {
+            let side = |list: &Vec<usize>| {
+                let mut out: Vec<usize> = list.iter().filter(|x| **x != other).cloned().collect();
+                out.sort();
+                out
+            };
+            (side(&element.positive), side(&element.negative))
+        };
+
+        let elements: Vec<Element> = self.elements.iter().map(|x| x.borrow().clone()).collect();
+        let mut removed: Vec<usize> = Vec::new();
+        for (i, a) in elements.iter().enumerate() {
+            if a.class == Ground || removed.contains(&a.id) {
+                continue;
+            }
+            for b in elements[i + 1..].iter() {
+                if b.class == a.class
+                    && b.value == a.value
+                    && !removed.contains(&b.id)
+                    && connections(a, b.id) == connections(b, a.id)
+                {
+                    removed.push(b.id);
+                }
+            }
+        }
+
+        removed.sort();
+        for id in removed.iter() {
+            self.remove_element(*id);
+        }
+        removed
+    }
+
+    /// Drop an element and every connection to it, the other ids are left as they are.
+    ///
+    /// Tools are cleared and have to be created again.
+    fn remove_element(&mut self, id: usize) {
+        if let Some(index) = self.index.get(&id) {
+            self.elements.remove(*index);
+        }
+        for element in self.elements.iter() {
+            let mut element = element.borrow_mut();
+            let element: &mut Element = &mut element;
+            for side in [&mut element.positive, &mut element.negative] {
+                side.retain(|x| *x != id);
+            }
+        }
+        self.reindex();
+        self.tools.clear();
+    }
+
+    /// Values of three distinct resistors taking part in a delta/wye transformation.
+    fn transform_values(&self, ids: &[usize; 3]) -> Result<Vec<f64>, StatusError> {
+        if ids[0] == ids[1] || ids[1] == ids[2] || ids[0] == ids[2] {
+            return Err(Known(format!("Resistors {:?} must be distinct", ids)));
+        }
+        ids.iter()
+            .map(|id| match self.get_element_by_id(*id) {
+                Some(element) if element.borrow().class == Resistor => Ok(element.borrow().value),
+                _ => Err(Known(format!("Element {} is not a resistor", id))),
+            })
+            .collect()
+    }
+
+    /// The connection list of element `from` on the side facing element `to`.
+    fn terminal_towards(&self, from: usize, to: usize) -> Result<Vec<usize>, StatusError> {
+        let element = self.get_element_by_id(from).unwrap().borrow();
+        match (
+            element.positive.contains(&to),
+            element.negative.contains(&to),
+        ) {
+            (true, false) => Ok(element.positive.clone()),
+            (false, true) => Ok(element.negative.clone()),
+            _ => Err(Known(format!(
+                "Resistors {} and {} do not share a single node",
+                from, to
+            ))),
+        }
+    }
+
+    /// Rewrite the connection lists of every element on the given outer nodes.
+    fn rewire_members<F>(&mut self, outer: &Vec<Vec<usize>>, replace: F)
+    where
+        F: Fn(&Vec<usize>) -> Vec<usize>,
+    {
+        let mut members: Vec<usize> = outer.concat();
+        members.sort();
+        members.dedup();
+        for id in members {
+            let mut element = self.get_element_by_id(id).unwrap().borrow_mut();
+            element.positive = replace(&element.positive);
+            element.negative = replace(&element.negative);
+        }
+    }
+
+    /// Current sources with a side that no other current can flow through.
+    ///
+    /// That side is either open or connected only to current sources, which is only
+    /// consistent for a single series source of the same value. A current source directly
+    /// across a voltage source is reported too, the voltage source fixes its voltage and
+    /// the current source has no effect on the rest of the circuit.
+    fn current_source_errors(&self) -> Vec<StatusError> {
+        let mut errors: Vec<StatusError> = Vec::new();
+        for source in self.elements.iter().map(|x| x.borrow()) {
+            if source.class != CurrentSrc || !source.enabled {
+                continue;
+            }
+            for side in [&source.positive, &source.negative] {
+                let neighbours: Vec<Element> = side
+                    .iter()
+                    .filter_map(|x| self.get_element_by_id(*x))
+                    .map(|x| x.borrow().clone())
+                    .filter(|x| x.enabled)
+                    .collect();
+                if neighbours.is_empty() {
+                    errors.push(Known(format!(
+                        "Current source {} feeds an open node",
+                        source.id
+                    )));
+                } else if neighbours.iter().all(|x| x.class == CurrentSrc)
+                    && (neighbours.len() > 1 || neighbours[0].value != source.value)
+                {
+                    errors.push(Known(format!(
+                        "Current source {} is in series with other current sources",
+                        source.id
+                    )));
+                }
+            }
+
+            // Both terminals touch the voltage source, directly or through ground
+            let touches = |side: &Vec<usize>, other: &Element| -> bool {
+                side.contains(&other.id)
+                    || (side.contains(&self.ground)
+                        && (other.positive.contains(&self.ground)
+                            || other.negative.contains(&self.ground)))
+            };
+            for other in self.elements.iter().map(|x| x.borrow()) {
+                if other.class == VoltageSrc
+                    && other.enabled
+                    && touches(&source.positive, &other)
+                    && touches(&source.negative, &other)
+                {
+                    errors.push(Known(format!(
+                        "Current source {} is in parallel with voltage source {}",
+                        source.id, other.id
+                    )));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Connections (A, B) where A lists B but B lists A on neither side.
+    fn asymmetric_connections(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for element in self.elements.iter() {
+            let element = element.borrow();
+            for other in element.positive.iter().chain(element.negative.iter()) {
+                let reciprocated: bool = self.get_element_by_id(*other).map_or(false, |x| {
+                    let x = x.borrow();
+                    x.positive.contains(&element.id) || x.negative.contains(&element.id)
+                });
+                if !reciprocated && !pairs.contains(&(element.id, *other)) {
+                    pairs.push((element.id, *other));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Check if both terminals of a source land on the same node.
+    ///
+    /// A source tied to ground is only shorted if both sides reach ground, otherwise
+    /// it is shorted when a single node Tool holds the source and both of its sides.
+    fn source_is_shorted(&self, source: &Element) -> bool {
+        let positive_ground: bool = source.positive.contains(&self.ground);
+        let negative_ground: bool = source.negative.contains(&self.ground);
+        if positive_ground || negative_ground {
+            return positive_ground && negative_ground;
+        }
+        if source.positive.is_empty() || source.negative.is_empty() {
+            return false;
+        }
+
+        let nodes: Vec<Rc<RefCell<Tool>>> = self
+            .tools
+            .iter()
+            .filter(|x| {
+                x.borrow().class == ToolType::Node && x.borrow().member_ids().contains(&source.id)
+            })
+            .cloned()
+            .collect();
+
+        match nodes.as_slice() {
+            [node] => {
+                let member_ids: Vec<usize> = node.borrow().member_ids();
+                source
+                    .positive
+                    .iter()
+                    .chain(source.negative.iter())
+                    .all(|x| member_ids.contains(x))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Small xorshift generator, enough to spread Monte Carlo samples
+struct XorShift(u64);
+
+impl XorShift {
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Side of the element that connects to `other` alone, true for the positive side.
+fn shared_side(element: &Element, other: usize) -> Option<bool> {
+    match (element.positive == [other], element.negative == [other]) {
+        (true, false) if !element.negative.contains(&other) => Some(true),
+        (false, true) if !element.positive.contains(&other) => Some(false),
+        _ => None,
+    }
 }
 
 impl Validation for Container {
@@ -339,12 +2468,21 @@ impl Validation for Container {
     ///
     /// * All Elements have a valid Component, Value, Positive, and Negative
     /// * No duplicate Elements or Tools
+    /// * Contains at least one element
     /// * Contains at least one source and a single ground
     /// * No floating Elements, Tools, etc.
     /// * No shorted or open Elements
+    /// * Every connection is listed by both Elements
+    /// * No current source feeds an open node
+    /// * No current source sits directly across a voltage source
+    /// * No element has both terminals on ground
     fn validate(&self) -> ValidationResult {
         let mut errors: Vec<StatusError> = Vec::new();
 
+        if self.elements.is_empty() {
+            return Err(Known("Empty circuit".to_string()));
+        }
+
         // Check that all elements and tools are valid individually
         errors.append(&mut get_all_internal_status_errors(&self.elements));
         errors.append(&mut get_all_internal_status_errors(&self.tools));
@@ -357,14 +2495,57 @@ impl Validation for Container {
         if !self.elements.iter().any(|x| x.borrow().class.is_source()) {
             errors.push(Known("No Sources".parse().unwrap()));
         }
-        if self
+        match self
             .elements
             .iter()
             .filter(|x| x.borrow().class == Ground)
             .count()
-            != 1
         {
-            errors.push(Known("Multiple Grounds".parse().unwrap()));
+            0 => errors.push(Known("No Ground".to_string())),
+            1 => {}
+            _ => errors.push(Known("Multiple Grounds".to_string())),
+        }
+
+        // Check that connections go both ways, one sided ones break node detection
+        let asymmetric: Vec<(usize, usize)> = self.asymmetric_connections();
+        if !asymmetric.is_empty() {
+            let pairs: Vec<String> = asymmetric
+                .iter()
+                .map(|(a, b)| format!("({}, {})", a, b))
+                .collect();
+            errors.push(Known(format!(
+                "One-directional connections: {}",
+                pairs.join(", ")
+            )));
+        }
+
+        // Check that no element is tied to ground on both sides, sources are checked below
+        for element in self.elements.iter().map(|x| x.borrow()) {
+            let grounded =
+                |side: &Vec<usize>| !side.is_empty() && side.iter().all(|x| *x == self.ground);
+            if element.class != VoltageSrc
+                && grounded(&element.positive)
+                && grounded(&element.negative)
+            {
+                errors.push(Known(format!(
+                    "Element {} has both terminals on ground",
+                    element.id
+                )));
+            }
+        }
+
+        // Check that every current source has a path for its current
+        errors.append(&mut self.current_source_errors());
+
+        // Check that no voltage source has both terminals on the same node, 0V sources are wires
+        for source in self.get_voltage_sources() {
+            let source = source.upgrade().unwrap();
+            if source.borrow().value != 0.0 && self.source_is_shorted(&source.borrow()) {
+                errors.push(Known(format!(
+                    "Shorted voltage source: {}",
+                    source.borrow().id
+                )));
+            }
         }
 
         match errors.len() {
@@ -381,42 +2562,463 @@ impl Validation for Container {
 
 #[cfg(test)]
 mod tests {
-    use crate::component::Component::{Ground, Resistor};
-    use crate::container::Container;
+    use crate::assert_known_error;
+    use crate::component::Component::{CurrentSrc, Ground, Resistor, VoltageSrc};
+    use crate::component::{Component, Simplification};
+    use crate::container::{
+        Container, NamingScheme, PortResult, SimplificationOpportunity, SimplificationStep,
+        TopologyHint,
+    };
     use crate::elements::Element;
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::{steps_to_markdown, Solver};
     use crate::tools::ToolType::{Mesh, SuperNode};
     use crate::util::*;
     use crate::validation::Status::Valid;
-    use crate::validation::{StatusError, Validation};
+    use crate::validation::StatusError::Known;
+    use crate::validation::{Status, StatusError, Validation, ValidationMode};
+    use nalgebra::DMatrix;
     use regex_lite::Regex;
+    use serde_json::json;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_debug() {
+        let re = Regex::new(
+            r#"Container \{ elements: \["R0: 1 Ω", "R1: 1 Ω"], tools: \[], state: .+\) }"#,
+        )
+        .unwrap();
+
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![3]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![3]));
+        println!("{:?}", container);
+        assert!(re.is_match(&format!("{:?}", container)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut container = create_basic_container();
+        assert_eq!(container.validate(), Ok(Valid));
+
+        // Test no sources
+        container.elements.remove(3);
+        assert!(container.validate().is_err());
+
+        // Test multiple grounds
+        container = create_basic_container();
+        container.add_element_no_id(Element::new(Ground, 1.0, vec![2], vec![]));
+        assert!(container.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        assert_known_error!(Container::new().validate(), "Empty circuit");
+
+        // Zero grounds is not reported as multiple grounds
+        let container = create_basic_container();
+        container.elements[0].borrow_mut().class = Resistor;
+        let errors: Vec<StatusError> = match container.validate() {
+            Err(StatusError::Multiple(errors)) => errors,
+            Err(error) => vec![error],
+            Ok(_) => vec![],
+        };
+        assert!(errors.contains(&Known("No Ground".to_string())));
+        assert!(!errors.contains(&Known("Multiple Grounds".to_string())));
+    }
+
+    #[test]
+    fn test_validate_parallel_sources() {
+        let parallel = |edges| Container::from_adjacency(edges).map(|_| ());
+        assert_known_error!(
+            parallel(vec![
+                (1, 0, Resistor, 2.0),
+                (1, 0, VoltageSrc, 5.0),
+                (1, 0, CurrentSrc, 1.0)
+            ]),
+            "Current source 3 is in parallel with voltage source 2"
+        );
+        assert_known_error!(
+            parallel(vec![
+                (1, 0, Resistor, 2.0),
+                (2, 0, Resistor, 2.0),
+                (1, 2, VoltageSrc, 5.0),
+                (2, 1, CurrentSrc, 1.0)
+            ]),
+            "Current source 4 is in parallel with voltage source 3"
+        );
+
+        // Sharing a single node is fine
+        assert!(parallel(vec![
+            (1, 0, Resistor, 2.0),
+            (2, 0, Resistor, 2.0),
+            (1, 2, VoltageSrc, 5.0),
+            (0, 2, CurrentSrc, 1.0)
+        ])
+        .is_ok());
+
+        // A disabled current source is left open, so it is not over-constrained
+        let mut container = Container::from_adjacency(vec![
+            (1, 0, Resistor, 2.0),
+            (1, 0, VoltageSrc, 5.0),
+            (1, 0, Resistor, 4.0),
+        ])
+        .unwrap();
+        container.elements[3].borrow_mut().class = CurrentSrc;
+        container.elements[3].borrow_mut().enabled = false;
+        assert_eq!(container.validate(), Ok(Valid));
+        container.elements[3].borrow_mut().enabled = true;
+        assert_known_error!(
+            container.validate(),
+            "Current source 3 is in parallel with voltage source 2"
+        );
+    }
+
+    #[test]
+    fn test_validate_open_current_source() {
+        let mut container = create_basic_container();
+        container.add_element_no_id(Element::new(CurrentSrc, 1.0, vec![0], vec![]));
+        container.elements[0].borrow_mut().positive.push(4);
+        assert_known_error!(container.validate(), "Current source 4 feeds an open node");
+
+        // Two different currents forced through the same wire
+        let mut container = create_basic_container();
+        container.add_element_no_id(Element::new(CurrentSrc, 1.0, vec![0], vec![5]));
+        container.add_element_no_id(Element::new(CurrentSrc, 2.0, vec![4], vec![0]));
+        container.elements[0].borrow_mut().positive.extend([4, 5]);
+        assert!(container.validate().is_err());
+
+        container.elements[5].borrow_mut().value = 1.0;
+        assert_eq!(container.validate(), Ok(Valid));
+        assert!(create_mna_container_2().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_isolated_ground() {
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 1.0, vec![2], vec![3]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![1], vec![3]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![1]));
+        assert_known_error!(container.validate(), "Ground is not connected");
+    }
+
+    #[test]
+    fn test_validate_shorted_source() {
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1], vec![]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![0]));
+        container.add_element_no_id(Element::new(VoltageSrc, 1.0, vec![1], vec![1]));
+        assert_eq!(container.validate(), Ok(Valid));
+
+        container.create_nodes().unwrap();
+        assert_eq!(
+            container.validate(),
+            Err(Known("Shorted voltage source: 2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_validate_one_directional() {
+        let mut container = create_basic_container();
+        assert_eq!(container.validate(), Ok(Valid));
+
+        container.elements[2].borrow_mut().positive.clear();
+        container.elements[1].borrow_mut().negative.push(0);
+        assert_eq!(
+            container.validate(),
+            Err(Known(
+                "One-directional connections: (1, 2), (1, 0)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_delta_wye() {
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![2, 3, 4, 5], vec![]));
+        container.add_element_no_id(Element::new(Resistor, 3.0, vec![3, 4], vec![2, 5]));
+        container.add_element_no_id(Element::new(Resistor, 3.0, vec![1, 5], vec![0, 3, 4, 5]));
+        container.add_element_no_id(Element::new(Resistor, 3.0, vec![0, 2, 4, 5], vec![1, 4]));
+        container.add_element_no_id(Element::new(VoltageSrc, 1.0, vec![1, 3], vec![0, 2, 3, 5]));
+        container.add_element_no_id(Element::new(Resistor, 3.0, vec![1, 2], vec![0, 2, 3, 4]));
+        assert_eq!(container.validate(), Ok(Valid));
+
+        container.delta_to_wye(1, 2, 3).unwrap();
+        assert_eq!(container.validate(), Ok(Valid));
+        for (id, centre) in [(1, vec![2, 3]), (2, vec![3, 1]), (3, vec![1, 2])] {
+            let element = container.get_element_by_id(id).unwrap().borrow();
+            assert_eq!(element.value, 1.0);
+            assert_eq!(element.negative, centre);
+        }
+        assert_eq!(
+            container.get_element_by_id(0).unwrap().borrow().positive,
+            vec![4, 5, 2]
+        );
+
+        container.wye_to_delta(1, 2, 3).unwrap();
+        assert_eq!(container.validate(), Ok(Valid));
+        for id in 1..4 {
+            assert_eq!(container.get_element_by_id(id).unwrap().borrow().value, 3.0);
+        }
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 2);
+
+        assert!(container.delta_to_wye(1, 1, 2).is_err());
+        assert!(container.delta_to_wye(1, 2, 4).is_err());
+    }
+
+    #[test]
+    fn test_collapse_series_sources() {
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1, 3], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 5.0, vec![2], vec![0, 3]));
+        container.add_element_no_id(Element::new(VoltageSrc, 3.0, vec![3], vec![1]));
+        container.add_element_no_id(Element::new(Resistor, 2.0, vec![2], vec![0, 1]));
+        assert_eq!(Simplification::Series.simplify(&mut container), Ok(1));
+
+        assert_eq!(container.elements.len(), 3);
+        let source = container.get_element_by_id(1).unwrap().borrow();
+        assert_eq!(source.value, 8.0);
+        assert_eq!(source.positive, vec![3]);
+        assert_eq!(source.negative, vec![0, 3]);
+        assert!(container.get_element_by_id(2).is_none());
+        let resistor = container.get_element_by_id(3).unwrap().borrow();
+        assert_eq!((resistor.id, resistor.class.clone()), (3, Resistor));
+        assert_eq!(resistor.positive, vec![1]);
+        assert_eq!(resistor.negative, vec![0, 1]);
+        assert_eq!(
+            container.get_element_by_id(0).unwrap().borrow().positive,
+            vec![1, 3]
+        );
+        drop((source, resistor));
+        assert_eq!(container.validate(), Ok(Valid));
+
+        // Opposing sources subtract and the result keeps the larger one's polarity
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1, 3], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 3.0, vec![2], vec![0, 3]));
+        container.add_element_no_id(Element::new(VoltageSrc, 5.0, vec![1], vec![3]));
+        container.add_element_no_id(Element::new(Resistor, 2.0, vec![2], vec![0, 1]));
+        assert_eq!(Simplification::Series.simplify(&mut container), Ok(1));
+        let source = container.get_element_by_id(1).unwrap().borrow();
+        assert_eq!(source.value, 2.0);
+        assert_eq!(source.positive, vec![0, 3]);
+        assert_eq!(source.negative, vec![3]);
+        drop(source);
+
+        assert_eq!(Simplification::Series.simplify(&mut container), Ok(0));
+        assert_eq!(Simplification::Parallel.simplify(&mut container), Ok(0));
+        assert!(Simplification::Norton.simplify(&mut container).is_err());
+    }
+
+    #[test]
+    fn test_millman_voltage() {
+        // Two 2Ω branches fed by 10V and 5V and a 4Ω load all meet at one node
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1, 3, 5], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 10.0, vec![2], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 2.0, vec![1], vec![4, 5]));
+        container.add_element_no_id(Element::new(VoltageSrc, 5.0, vec![4], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 2.0, vec![3], vec![2, 5]));
+        container.add_element_no_id(Element::new(Resistor, 4.0, vec![2, 4], vec![0]));
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+
+        let load: Element = container.get_element_by_id(5).unwrap().borrow().clone();
+        let node: usize = container.terminal_node(&load, &load.positive).unwrap();
+        let millman: f64 = container.millman_voltage(node).unwrap();
+        assert!((millman - 6.0).abs() < 1e-9);
+
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve().unwrap();
+        let index: usize = container.borrow().node_index(node).unwrap();
+        let solved: f64 = container.borrow().get_tool_by_id(index).borrow().value;
+        assert!((millman - solved).abs() < 1e-9);
+
+        // The source nodes are not fed through resistors
+        let source: Element = container
+            .borrow()
+            .get_element_by_id(1)
+            .unwrap()
+            .borrow()
+            .clone();
+        let source_node: usize = container
+            .borrow()
+            .terminal_node(&source, &source.positive)
+            .unwrap();
+        assert!(container.borrow().millman_voltage(source_node).is_err());
+        assert!(container.borrow().millman_voltage(10).is_err());
+    }
+
+    #[test]
+    fn test_deduplicate_elements() {
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1, 2, 3, 4], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 5.0, vec![2, 3, 4], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 10.0, vec![1, 3, 4], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 10.0, vec![1, 2, 4], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 20.0, vec![1, 2, 3], vec![0]));
+        assert_eq!(container.deduplicate_elements(), vec![3]);
+
+        assert_eq!(container.elements.len(), 4);
+        assert!(container.get_element_by_id(3).is_none());
+        assert_eq!(
+            container.get_element_by_id(0).unwrap().borrow().positive,
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            container.get_element_by_id(2).unwrap().borrow().positive,
+            vec![1, 4]
+        );
+        let last = container.get_element_by_id(4).unwrap().borrow().clone();
+        assert_eq!((last.id, last.value), (4, 20.0));
+        assert_eq!(last.positive, vec![1, 2]);
+        assert_eq!(container.validate(), Ok(Valid));
+
+        assert!(container.deduplicate_elements().is_empty());
+        assert!(create_mna_container().deduplicate_elements().is_empty());
+    }
+
+    #[test]
+    fn test_set_enabled() {
+        // 10V through 5Ω into two parallel 10Ω resistors
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![1, 3, 4], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 10.0, vec![2], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 5.0, vec![1], vec![3, 4]));
+        container.add_element_no_id(Element::new(Resistor, 10.0, vec![2, 4], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 10.0, vec![2, 3], vec![0]));
+
+        let series_current = |container: &Container| -> f64 {
+            let mut container: Container = container.clone();
+            container.create_nodes().unwrap();
+            container.create_super_nodes().unwrap();
+            let container = Rc::new(RefCell::new(container));
+            let mut solver: NodeStepSolver = Solver::new(container.clone());
+            solver.solve().unwrap();
+            let current: f64 = container.borrow().current_through(2).unwrap();
+            current.abs()
+        };
+        assert!((series_current(&container) - 1.0).abs() < 1e-9);
+
+        container.create_nodes().unwrap();
+        container.set_enabled(4, false).unwrap();
+        assert!(container.tools.is_empty());
+        container.create_nodes().unwrap();
+        assert!(container.nodes().iter().all(|x| !x
+            .upgrade()
+            .unwrap()
+            .borrow()
+            .member_ids()
+            .contains(&4)));
+        assert!((series_current(&container) - 10.0 / 15.0).abs() < 1e-9);
+
+        container.set_enabled(4, true).unwrap();
+        assert!((series_current(&container) - 1.0).abs() < 1e-9);
+        assert!(container.set_enabled(9, false).is_err());
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        // 10V over two 10Ω resistors, the output is the node between them
+        let mut container = create_divider_container(10.0, 10.0, 10.0);
+        container.create_nodes().unwrap();
+        let bottom: Element = container.get_element_by_id(3).unwrap().borrow().clone();
+        let output: usize = container.terminal_node(&bottom, &bottom.positive).unwrap();
+
+        let result = container.monte_carlo(200, 0.01);
+        assert_eq!(result.samples, 200);
+        assert_eq!(result.nodes.len(), 2);
+        let i: usize = result.nodes.iter().position(|x| *x == output).unwrap();
+        assert!((result.mean[i] - 5.0).abs() < 0.01);
+        assert!(result.std_dev[i] > 0.0);
+        assert!(result.std_dev[i] < 0.05);
+
+        // The source fixes the other node whatever the resistors are
+        assert!((result.mean[1 - i] - 10.0).abs() < 1e-9);
+        assert!(result.std_dev[1 - i] < 1e-9);
+
+        assert_eq!(container.monte_carlo(200, 0.01), result);
+        assert_eq!(container.get_element_by_id(2).unwrap().borrow().value, 10.0);
+    }
+
+    #[test]
+    fn test_status() {
+        let mut container = create_mna_container();
+        container.elements[2].borrow_mut().value = -1.0;
+        assert_eq!(container.status(), Status::New);
+        container.elements[2].borrow_mut().value = 4.0;
+        assert_eq!(container.status(), Status::Valid);
+
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+        assert_eq!(container.status(), Status::Valid);
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve().unwrap();
+        assert_eq!(container.borrow().status(), Status::Solved);
+
+        container.borrow_mut().create_nodes().unwrap();
+        assert_eq!(container.borrow().status(), Status::Valid);
+    }
 
     #[test]
-    fn test_debug() {
-        let re = Regex::new(
-            r#"Container \{ elements: \["R0: 1 Ω", "R1: 1 Ω"], tools: \[], state: .+\) }"#,
-        )
-        .unwrap();
+    fn test_verify_solved() {
+        let mut container = create_mna_container();
+        assert_known_error!(
+            container.verify_solved(),
+            "No nodes to verify, call create_nodes first"
+        );
+        container.create_nodes().unwrap();
+        assert_eq!(
+            container.verify_solved(),
+            Err(Known("Nodes without a finite value: 1, 2, 3".to_string()))
+        );
 
-        let mut container = Container::new();
-        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![3]));
-        container.add_element_no_id(Element::new(Resistor, 1.0, vec![2], vec![3]));
-        println!("{:?}", container);
-        assert!(re.is_match(&format!("{:?}", container)));
+        container.get_tool_by_id(0).borrow_mut().set_value(1.0);
+        container
+            .get_tool_by_id(2)
+            .borrow_mut()
+            .set_value(f64::INFINITY);
+        assert_eq!(
+            container.verify_solved(),
+            Err(Known("Nodes without a finite value: 2, 3".to_string()))
+        );
+
+        container.get_tool_by_id(1).borrow_mut().set_value(0.5);
+        container.get_tool_by_id(2).borrow_mut().set_value(0.0);
+        assert_eq!(container.verify_solved(), Ok(()));
     }
 
     #[test]
-    fn test_validate() {
-        let mut container = create_basic_container();
-        assert_eq!(container.validate(), Ok(Valid));
+    fn test_naming_scheme() {
+        let mut container = Container::new();
+        container.set_naming_scheme(NamingScheme::PerClassSequential);
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![2, 3], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 1.0, vec![2], vec![0, 3]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![1], vec![0, 3]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![1, 2], vec![0]));
+
+        let second = container.get_element_by_id(3).unwrap().borrow();
+        assert_eq!(second.id, 3);
+        assert_eq!(second.basic_string(), "R2");
+        assert_eq!(
+            container.get_element_by_id(1).unwrap().basic_string(),
+            "SRC(V)1"
+        );
 
-        // Test no sources
-        container.elements.remove(3);
-        assert!(container.validate().is_err());
+        // The number survives a JSON round trip, the name is filled in again on loading
+        let json = serde_json::to_value(&*second).unwrap();
+        let mut copy = Container::new();
+        copy.add_element_no_id(serde_json::from_value(json).unwrap());
+        assert_eq!(copy.get_element_by_id(0).unwrap().basic_string(), "R2");
 
-        // Test multiple grounds
-        container = create_basic_container();
-        container.add_element_no_id(Element::new(Ground, 1.0, vec![2], vec![]));
-        assert!(container.validate().is_err());
+        let basic = create_basic_container();
+        assert_eq!(basic.get_element_by_id(2).unwrap().basic_string(), "R2");
+        assert_eq!(basic.get_element_by_id(1).unwrap().basic_string(), "R1");
     }
 
     #[test]
@@ -429,6 +3031,7 @@ mod tests {
         assert!(result.is_err());
 
         // Test add_element with valid element
+        container.elements[2].borrow_mut().positive.push(4);
         let result: Result<usize, StatusError> =
             container.add_element(Element::new(Resistor, 1.0, vec![2], vec![]));
 
@@ -454,6 +3057,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_nodes_idempotent() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 2);
+        assert_eq!(container.tools.len(), 2);
+
+        let mut incremental = create_basic_container();
+        for id in 0..incremental.elements.len() {
+            incremental.add_node_for_element(id).unwrap();
+        }
+        assert_eq!(incremental.nodes().len(), 2);
+        for (a, b) in incremental.tools.iter().zip(container.tools.iter()) {
+            let mut expected: Vec<usize> = b.borrow().member_ids();
+            let mut actual: Vec<usize> = a.borrow().member_ids();
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected);
+        }
+        assert!(incremental.add_node_for_element(10).is_err());
+
+        // Supernodes and labels survive a rebuild, the nodes stay in front
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+        container.label_node(2, "VOUT").unwrap();
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 3);
+        assert_eq!(container.get_tools(SuperNode).len(), 1);
+        assert_eq!(container.tools[3].borrow().id, 4);
+        assert_eq!(
+            container.get_tool_by_id(1).borrow().label,
+            Some("VOUT".to_string())
+        );
+        container.create_super_nodes().unwrap();
+        assert_eq!(container.tools.len(), 4);
+    }
+
     #[test]
     fn test_get_nodes() {
         let mut x = create_basic_container();
@@ -529,6 +3171,825 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_mesh_basis() {
+        let mut basic: Container = create_basic_container();
+        basic.create_nodes().unwrap();
+        assert_known_error!(
+            basic.verify_mesh_basis(),
+            "Found 0 meshes but the circuit has 1 independent loops"
+        );
+        basic.create_meshes();
+        assert_eq!(basic.get_tools(Mesh).len(), 1);
+        assert_eq!(basic.verify_mesh_basis(), Ok(()));
+
+        let mut mna: Container = create_mna_container();
+        mna.create_nodes().unwrap();
+        mna.create_meshes();
+        assert_eq!(mna.get_tools(Mesh).len(), 2);
+        assert_eq!(mna.verify_mesh_basis(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_ground_to_ground() {
+        let mut container = create_basic_container();
+        let id: usize = container.add_element_no_id(Element::new(Resistor, 1.0, vec![0], vec![0]));
+        container.elements[0].borrow_mut().positive.push(id);
+        assert_known_error!(
+            container.validate(),
+            "Element 4 has both terminals on ground"
+        );
+
+        container.create_nodes().unwrap();
+        let pairs = container.get_all_node_pairs();
+        assert_eq!(pairs.len(), 3);
+        assert!(pairs.iter().all(|x| x.2.borrow().id != id));
+    }
+
+    #[test]
+    fn test_clone_with_id_offset() {
+        let basic: Container = create_basic_container();
+        let first: Container = basic.clone_with_id_offset(0);
+        let second: Container = basic.clone_with_id_offset(4);
+        assert_eq!(second.ground, 4);
+        assert!(second.tools.is_empty());
+
+        let mut merged: Container = Container::new();
+        merged.elements = first.elements.clone();
+        merged.elements.extend(second.elements.iter().cloned());
+        merged.reindex();
+        for (i, element) in merged.elements.iter().enumerate() {
+            assert_eq!(element.borrow().id, i);
+        }
+
+        // The copy is connected like the original, only shifted
+        for (original, shifted) in basic.elements.iter().zip(second.elements.iter()) {
+            let (original, shifted) = (original.borrow(), shifted.borrow());
+            let shift = |side: &Vec<usize>| side.iter().map(|x| x + 4).collect::<Vec<usize>>();
+            assert_eq!(shifted.positive, shift(&original.positive));
+            assert_eq!(shifted.negative, shift(&original.negative));
+        }
+        assert!(merged.asymmetric_connections().is_empty());
+        assert_eq!(basic.elements[1].borrow().id, 1);
+    }
+
+    #[test]
+    fn test_simplification_opportunities() {
+        // R2 and R3 in series on their own node, R4 and R5 in parallel to ground
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0., vec![1, 4, 5], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 10., vec![2], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 1., vec![1], vec![3]));
+        container.add_element_no_id(Element::new(Resistor, 2., vec![2], vec![4, 5]));
+        container.add_element_no_id(Element::new(Resistor, 4., vec![3, 5], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 4., vec![3, 4], vec![0]));
+        assert_eq!(container.validate(), Ok(Valid));
+
+        let opportunities = container.simplification_opportunities();
+        assert_eq!(
+            opportunities,
+            vec![
+                SimplificationOpportunity {
+                    kind: Simplification::Series,
+                    element_ids: vec![2, 3],
+                    equivalent: 3.,
+                },
+                SimplificationOpportunity {
+                    kind: Simplification::Parallel,
+                    element_ids: vec![4, 5],
+                    equivalent: 2.,
+                },
+            ]
+        );
+        assert_eq!(container.elements.len(), 6);
+        assert!(create_mna_container()
+            .simplification_opportunities()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_solve_for_source() {
+        let mut divider = create_divider_container(10., 1., 3.);
+        divider.create_nodes().unwrap();
+        let load: Element = divider.get_element_by_id(3).unwrap().borrow().clone();
+        let middle: usize = divider.terminal_node(&load, &load.positive).unwrap();
+
+        // The middle node reads 3/4 of the source
+        let value: f64 = divider.solve_for_source(1, middle, 3.0).unwrap();
+        assert!((value - 4.0).abs() < 1e-9);
+        assert_eq!(divider.get_element_by_id(1).unwrap().borrow().value, 10.);
+
+        assert_known_error!(
+            divider.solve_for_source(2, middle, 3.0),
+            "Element 2 is not a source"
+        );
+        assert_known_error!(divider.solve_for_source(1, 9, 3.0), "Node 9 does not exist");
+    }
+
+    #[test]
+    fn test_merge() {
+        let divider = |source: f64, resistance: f64| -> Container {
+            create_divider_container(source, resistance, resistance)
+        };
+
+        // Join the middle nodes, (10/1 + 4/2) / (1/1 + 1/1 + 1/2 + 1/2) = 4V
+        let mut merged: Container = divider(10., 1.);
+        merged.merge(divider(4., 2.), vec![(3, 3)]).unwrap();
+        assert_eq!(merged.elements.len(), 7);
+        assert_eq!(merged.validate(), Ok(Valid));
+        assert_eq!(
+            merged.get_element_by_id(0).unwrap().borrow().positive,
+            vec![1, 3, 5, 7]
+        );
+        assert!(merged.get_element_by_id(4).is_none());
+
+        merged.create_nodes().unwrap();
+        merged.create_super_nodes().unwrap();
+        let merged = Rc::new(RefCell::new(merged));
+        let mut solver: NodeStepSolver = Solver::new(merged.clone());
+        solver.solve().unwrap();
+        assert!((merged.borrow().voltage_across(3).unwrap().abs() - 4.0).abs() < 1e-9);
+        assert!((merged.borrow().voltage_across(7).unwrap().abs() - 4.0).abs() < 1e-9);
+
+        assert_known_error!(
+            divider(1., 1.).merge(divider(1., 1.), vec![(3, 9)]),
+            "Element 9 does not exist in the merged container"
+        );
+    }
+
+    #[test]
+    fn test_resistor_ladder() {
+        let mut ladder: Container = Container::resistor_ladder(3, 1.0, 10.0);
+        assert_eq!(ladder.elements.len(), 8);
+        assert_eq!(ladder.validate(), Ok(Valid));
+        ladder.create_nodes().unwrap();
+        ladder.create_super_nodes().unwrap();
+        assert_eq!(ladder.nodes().len(), 4);
+
+        let ladder = Rc::new(RefCell::new(ladder));
+        let mut solver: NodeStepSolver = Solver::new(ladder.clone());
+        solver.solve().unwrap();
+
+        // The source node, then the node after every series resistor
+        let ladder = ladder.borrow();
+        let mut voltages: Vec<f64> = Vec::new();
+        for (id, positive) in [(2, true), (2, false), (4, false), (6, false)] {
+            let element: Element = ladder.get_element_by_id(id).unwrap().borrow().clone();
+            let side: &Vec<usize> = if positive {
+                &element.positive
+            } else {
+                &element.negative
+            };
+            let node: usize = ladder.terminal_node(&element, side).unwrap();
+            let index: usize = ladder.node_index(node).unwrap();
+            voltages.push(ladder.get_tool_by_id(index).borrow().value);
+        }
+        assert!((voltages[0] - 10.0).abs() < 1e-9);
+        assert!(voltages.windows(2).all(|x| x[0] > x[1] && x[1] > 0.0));
+    }
+
+    #[test]
+    fn test_simplify() {
+        // R3 and R4 in series, together in parallel with R5, then in series with R2
+        let mut container: Container = Container::from_adjacency(vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 2.0),
+            (2, 3, Resistor, 3.0),
+            (3, 0, Resistor, 3.0),
+            (2, 0, Resistor, 6.0),
+        ])
+        .unwrap();
+        let steps: Vec<SimplificationStep> = container.simplify();
+        let step =
+            |kind: Simplification, element_ids: Vec<usize>, kept, equivalent| SimplificationStep {
+                kind,
+                element_ids,
+                kept,
+                equivalent,
+            };
+        assert_eq!(
+            steps,
+            vec![
+                step(Simplification::Series, vec![3, 4], 3, 6.0),
+                step(Simplification::Parallel, vec![3, 5], 3, 3.0),
+                step(Simplification::Series, vec![2, 3], 2, 5.0),
+            ]
+        );
+
+        assert_eq!(container.elements.len(), 3);
+        assert_eq!(container.validate(), Ok(Valid));
+        let resistor: Element = container.get_element_by_id(2).unwrap().borrow().clone();
+        assert_eq!(resistor.value, 5.0);
+        assert_eq!((resistor.positive, resistor.negative), (vec![1], vec![0]));
+        assert!(container.simplify().is_empty());
+
+        // One kind at a time the same network takes a series, a parallel and a series pass
+        let mut container: Container = Container::from_adjacency(vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 2.0),
+            (2, 3, Resistor, 3.0),
+            (3, 0, Resistor, 3.0),
+            (2, 0, Resistor, 6.0),
+        ])
+        .unwrap();
+        assert_eq!(Simplification::Series.simplify(&mut container), Ok(1));
+        assert_eq!(Simplification::Parallel.simplify(&mut container), Ok(1));
+        assert_eq!(Simplification::Series.simplify(&mut container), Ok(1));
+        assert_eq!(Simplification::Parallel.simplify(&mut container), Ok(0));
+        assert_eq!(container.get_element_by_id(2).unwrap().borrow().value, 5.0);
+    }
+
+    #[test]
+    fn test_port_analysis() {
+        // R3 is the load, R4 stays across it as part of the source network
+        let mut container: Container = Container::from_adjacency(vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 2.0),
+            (2, 0, Resistor, 3.0),
+            (2, 0, Resistor, 6.0),
+        ])
+        .unwrap();
+        let port: PortResult = container.port_analysis(3).unwrap();
+        assert!((port.thevenin_voltage - 7.5).abs() < 1e-9);
+        assert!((port.thevenin_resistance - 1.5).abs() < 1e-9);
+        assert!((port.norton_current - 5.0).abs() < 1e-9);
+        assert!((port.load_current.abs() - 5.0 / 3.0).abs() < 1e-9);
+
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve().unwrap();
+        let across: f64 = container.borrow().voltage_across(3).unwrap();
+        assert!((port.load_voltage - across).abs() < 1e-9);
+        assert!((across.abs() - 5.0).abs() < 1e-9);
+
+        assert_known_error!(
+            container.borrow().port_analysis(1),
+            "Element 1 is not a resistor"
+        );
+    }
+
+    #[test]
+    fn test_from_adjacency() {
+        // Balanced Wheatstone bridge, R6 spans the middle nodes
+        let mut bridge: Container = Container::from_adjacency(vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 1.0),
+            (1, 3, Resistor, 2.0),
+            (2, 0, Resistor, 1.0),
+            (3, 0, Resistor, 2.0),
+            (2, 3, Resistor, 5.0),
+        ])
+        .unwrap();
+        assert_eq!(bridge.elements.len(), 7);
+        assert_eq!(bridge.elements[0].borrow().positive, vec![1, 4, 5]);
+        assert_eq!(bridge.elements[2].borrow().negative, vec![4, 6]);
+        bridge.create_nodes().unwrap();
+        bridge.create_super_nodes().unwrap();
+        assert_eq!(bridge.nodes().len(), 3);
+
+        let bridge = Rc::new(RefCell::new(bridge));
+        let mut solver: NodeStepSolver = Solver::new(bridge.clone());
+        solver.solve().unwrap();
+        let bridge = bridge.borrow();
+        assert!(bridge.current_through(6).unwrap().abs() < 1e-9);
+        assert!((bridge.current_through(2).unwrap().abs() - 5.0).abs() < 1e-9);
+        assert!((bridge.voltage_across(4).unwrap().abs() - 5.0).abs() < 1e-9);
+
+        assert!(Container::from_adjacency(vec![(1, 1, Resistor, 1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_supernode_terminals() {
+        // The floating source points from node 2 back to node 1
+        let circuit = |sources: Vec<(usize, usize, f64)>| -> Container {
+            let mut edges = vec![(1, 0, Resistor, 2.0), (2, 0, Resistor, 4.0)];
+            edges.extend(sources.into_iter().map(|(a, b, v)| (a, b, VoltageSrc, v)));
+            let mut container: Container = Container::from_adjacency(edges).unwrap();
+            container.create_nodes().unwrap();
+            container
+        };
+
+        let mut reversed: Container = circuit(vec![(2, 1, 10.0)]);
+        reversed.create_super_nodes().unwrap();
+        let super_node = reversed.get_tools(SuperNode)[0].upgrade().unwrap();
+        assert_eq!(super_node.borrow().terminals, Some((2, 1)));
+
+        // V2 - V1 = 10 with V1/2 + V2/4 = 0
+        let reversed = Rc::new(RefCell::new(reversed));
+        let mut solver: NodeStepSolver = Solver::new(reversed.clone());
+        solver.solve().unwrap();
+        for (id, expected) in [(1, -10.0 / 3.0), (2, 20.0 / 3.0)] {
+            let index: usize = reversed.borrow().node_index(id).unwrap();
+            assert!((solver.node_voltages[index] - expected).abs() < 1e-9);
+        }
+
+        // Parallel sources only agree when they are wired the same way round
+        let mut agreeing: Container = circuit(vec![(2, 1, 10.0), (2, 1, 10.0)]);
+        assert!(agreeing.create_super_nodes().is_ok());
+        let mut conflicting: Container = circuit(vec![(2, 1, 10.0), (1, 2, 10.0)]);
+        assert_eq!(
+            conflicting.create_super_nodes().err(),
+            Some("V3 and V4 fix nodes 1 and 2 to different voltages".to_string())
+        );
+    }
+
+    #[test]
+    fn test_verify_references() {
+        let mut container: Container = create_basic_container();
+        container.create_nodes().unwrap();
+        assert_eq!(container.verify_references(), Ok(()));
+
+        // The source leads the members of node 1, it dangles once its last Rc is gone
+        let source = container.elements.pop().unwrap();
+        assert_eq!(container.verify_references(), Ok(()));
+        drop(source);
+        assert_known_error!(
+            container.verify_references(),
+            "Dangling references: tool 1 member 0"
+        );
+    }
+
+    #[test]
+    fn test_branch_count() {
+        let mut mna: Container = create_mna_container();
+        assert_eq!(mna.get_elements().len(), 6);
+        assert_eq!(mna.branch_count(), 5);
+        mna.set_enabled(3, false).unwrap();
+        assert_eq!(mna.branch_count(), 4);
+    }
+
+    #[test]
+    fn test_all_cycles() {
+        let mut basic: Container = create_basic_container();
+        basic.create_nodes().unwrap();
+        let cycles: Vec<Vec<usize>> = basic.all_cycles();
+        assert_eq!(cycles.len(), 1);
+        let mut members: Vec<usize> = cycles[0].clone();
+        members.sort();
+        assert_eq!(members, vec![1, 2, 3]);
+
+        // Consecutive elements in a loop share a node, including the last back to the first
+        let mut mna: Container = create_mna_container();
+        mna.create_nodes().unwrap();
+        let cycles: Vec<Vec<usize>> = mna.all_cycles();
+        assert_eq!(cycles.len(), 2);
+        let pairs = mna.get_all_node_pairs();
+        let nodes = |id: usize| -> Vec<usize> {
+            let (a, b, _) = pairs.iter().find(|x| x.2.borrow().id == id).unwrap();
+            vec![*a, *b]
+        };
+        for cycle in cycles.iter() {
+            for (k, id) in cycle.iter().enumerate() {
+                let next: usize = cycle[(k + 1) % cycle.len()];
+                assert!(nodes(*id).iter().any(|x| nodes(next).contains(x)));
+            }
+        }
+        let mut all: Vec<usize> = cycles.concat();
+        all.sort();
+        all.dedup();
+        assert_eq!(all, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_shared_element() {
+        let mut basic: Container = create_basic_container();
+        basic.create_nodes().unwrap();
+        assert_eq!(basic.shared_element(1, 2), Some(1));
+        assert_eq!(basic.shared_element(1, 9), None);
+
+        // The basic container has a single mesh, the mna container has two side by side
+        let mut mna: Container = create_mna_container();
+        mna.create_nodes().unwrap();
+        mna.create_meshes();
+        assert_eq!(mna.shared_element(1, 2), Some(2));
+        assert_eq!(mna.shared_element(2, 3), Some(4));
+        let meshes: Vec<Vec<usize>> = mna
+            .get_tools(Mesh)
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().member_ids())
+            .collect();
+        let shared: usize = mna.shared_element(4, 5).unwrap();
+        assert!(meshes.iter().all(|x| x.contains(&shared)));
+        assert_eq!(
+            mna.get_element_by_id(shared).unwrap().borrow().class,
+            Resistor
+        );
+    }
+
+    #[test]
+    fn test_create_meshes_stable() {
+        let mesh_members = |container: &Container| -> Vec<Vec<usize>> {
+            container
+                .get_tools(Mesh)
+                .iter()
+                .map(|x| x.upgrade().unwrap().borrow().member_ids())
+                .collect()
+        };
+
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        container.create_meshes();
+        let expected: Vec<Vec<usize>> = mesh_members(&container);
+        assert!(!expected.is_empty());
+        for mesh in expected.iter() {
+            assert!(mesh.windows(2).all(|x| x[0] < x[1]));
+        }
+
+        for _ in 0..5 {
+            container.create_meshes();
+            assert_eq!(mesh_members(&container), expected);
+
+            let mut fresh = create_mna_container();
+            fresh.create_nodes().unwrap();
+            fresh.create_meshes();
+            assert_eq!(mesh_members(&fresh), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_planar() {
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        assert!(container.is_planar());
+
+        // Every pair of the four nodes and ground joined by a resistor forms K5
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0.0, vec![], vec![]));
+        let pairs: Vec<(usize, usize)> = (0..5)
+            .flat_map(|a| (a + 1..5).map(move |b| (a, b)))
+            .collect();
+        let mut terminals: Vec<Vec<usize>> = vec![vec![]; 5];
+        for (k, (a, b)) in pairs.iter().enumerate() {
+            terminals[*a].push(k + 1);
+            terminals[*b].push(k + 1);
+        }
+        for (k, (a, b)) in pairs.iter().enumerate() {
+            let id: usize = k + 1;
+            let others = |node: usize| -> Vec<usize> {
+                terminals[node]
+                    .iter()
+                    .filter(|x| **x != id)
+                    .cloned()
+                    .collect()
+            };
+            let mut negative: Vec<usize> = others(*a);
+            if *a == 0 {
+                negative.push(0);
+            }
+            container.add_element_no_id(Element::new(Resistor, 1.0, others(*b), negative));
+        }
+        container.elements[0].borrow_mut().positive = terminals[0].clone();
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 4);
+        assert!(!container.is_planar());
+    }
+
+    #[test]
+    fn test_topology_rank() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        assert_eq!(container.topology_rank(), container.nodes().len());
+
+        // Floating triangle of resistors with no path to ground
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![6], vec![5]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![4], vec![6]));
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![5], vec![4]));
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 5);
+        assert_eq!(container.topology_rank(), 4);
+    }
+
+    #[test]
+    fn test_supernode_report() {
+        let mut container = create_basic_supernode_container();
+        container.create_nodes().unwrap();
+        assert!(container.supernode_report().is_empty());
+
+        container.create_super_nodes().unwrap();
+        assert_eq!(container.supernode_report(), vec![(4, vec![1, 2])]);
+    }
+
+    #[test]
+    fn test_node_creation_steps() {
+        let mut container = create_basic_container();
+        container
+            .create_nodes()
+            .unwrap()
+            .create_super_nodes()
+            .unwrap();
+        let steps = container.node_creation_steps();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].title(), Some("Identify Nodes".to_string()));
+        let descriptions: Vec<Option<String>> = steps[0]
+            .sub_steps
+            .iter()
+            .map(|x| x.description.clone())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                Some("Node 1: R1, V3".to_string()),
+                Some("Node 2: R1, R2".to_string())
+            ]
+        );
+
+        let mut container = create_basic_supernode_container();
+        container
+            .create_nodes()
+            .unwrap()
+            .create_super_nodes()
+            .unwrap();
+        let steps = container.node_creation_steps();
+        assert_eq!(steps.len(), 2);
+        assert!(steps[1].sub_steps[0]
+            .description
+            .clone()
+            .unwrap()
+            .ends_with("joins N1, N2"));
+    }
+
+    #[test]
+    fn test_conductance_matrix() {
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        let expected =
+            DMatrix::from_row_slice(3, 3, &[0.25, -0.25, 0.0, -0.25, 0.375, 0.0, 0.0, 0.0, 0.5]);
+        assert_eq!(container.conductance_matrix(), expected);
+    }
+
+    #[test]
+    fn test_classify_topology() {
+        let classify = |edges: Vec<(usize, usize, Component, f64)>| -> TopologyHint {
+            let mut container: Container = Container::from_adjacency(edges).unwrap();
+            container.create_nodes().unwrap();
+            container.classify_topology()
+        };
+
+        let mut basic = create_basic_container();
+        basic.create_nodes().unwrap();
+        assert_eq!(basic.classify_topology(), TopologyHint::VoltageDivider);
+
+        let bridge = vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 1.0),
+            (1, 3, Resistor, 2.0),
+            (2, 0, Resistor, 1.0),
+            (3, 0, Resistor, 2.0),
+            (2, 3, Resistor, 5.0),
+        ];
+        assert_eq!(classify(bridge), TopologyHint::WheatstoneBridge);
+
+        let ladder = vec![
+            (1, 0, VoltageSrc, 10.0),
+            (1, 2, Resistor, 1.0),
+            (2, 0, Resistor, 2.0),
+            (2, 3, Resistor, 1.0),
+            (3, 0, Resistor, 2.0),
+        ];
+        assert_eq!(classify(ladder), TopologyHint::ResistorLadder);
+
+        let mut mna = create_mna_container();
+        mna.create_nodes().unwrap();
+        assert_eq!(mna.classify_topology(), TopologyHint::Unrecognized);
+    }
+
+    #[test]
+    fn test_label_node() {
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        container.label_node(1, "VOUT").unwrap();
+        assert_known_error!(container.label_node(9, "VIN"), "Node 9 does not exist");
+        assert_known_error!(container.label_node(2, " "), "Node labels cannot be empty");
+        container.create_super_nodes().unwrap();
+
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        let steps = solver.solve().unwrap();
+        assert!(steps_to_markdown(&steps[3..4]).contains("\\text{VOUT}"));
+        // The label must not move the node's column, every voltage matches the unlabeled solve
+        for (index, expected) in [20.0, -88.0 / 7.0, 136.0 / 7.0].into_iter().enumerate() {
+            let value: f64 = container.borrow().get_tool_by_id(index).borrow().value;
+            assert!(
+                (value - expected).abs() < 1e-9,
+                "N{} = {}",
+                index + 1,
+                value
+            );
+        }
+
+        let node = container.borrow().get_tool_by_id(0).borrow().clone();
+        assert_eq!(serde_json::to_value(&node).unwrap()["label"], json!("VOUT"));
+        let other = container.borrow().get_tool_by_id(1).borrow().clone();
+        assert!(serde_json::to_value(&other).unwrap().get("label").is_none());
+    }
+
+    #[test]
+    fn test_get_element_by_id() {
+        let mut container = Container::new();
+        for (id, value) in [(0, 0.0), (5, 1.0), (2, 2.0)] {
+            let mut element = Element::new(Resistor, value, vec![], vec![]);
+            element.id = id;
+            container.add_element_core(element);
+        }
+
+        assert_eq!(container.get_element_by_id(5).unwrap().borrow().value, 1.0);
+        assert_eq!(container.get_element_by_id(2).unwrap().borrow().value, 2.0);
+        assert_eq!(container.get_element_by_id(0).unwrap().borrow().value, 0.0);
+        assert!(container.get_element_by_id(1).is_none());
+
+        // Looking an element up does not borrow any of them
+        let held = container.elements[0].borrow_mut();
+        assert!(Rc::ptr_eq(
+            container.get_element_by_id(5).unwrap(),
+            &container.elements[1]
+        ));
+        drop(held);
+    }
+
+    #[test]
+    fn test_validate_non_positional_ids() {
+        // The basic circuit with R2 renumbered to 7
+        let renumber = |x: &mut usize| {
+            if *x == 2 {
+                *x = 7
+            }
+        };
+        let mut container = Container::new();
+        for element in create_basic_container().get_elements() {
+            let mut element: Element = element.borrow().clone();
+            renumber(&mut element.id);
+            element.positive.iter_mut().for_each(renumber);
+            element.negative.iter_mut().for_each(renumber);
+            container.add_element_core(element);
+        }
+        assert_eq!(container.validate(), Ok(Valid));
+        assert_eq!(
+            container.get_element_by_id(7).unwrap().borrow().class,
+            Resistor
+        );
+        assert!(container.get_element_by_id(2).is_none());
+
+        // New elements take the next free id
+        let id: usize = container.add_element_no_id(Element::new(Resistor, 1.0, vec![], vec![]));
+        assert_eq!(id, 8);
+        assert!(Rc::ptr_eq(
+            container.get_element_by_id(8).unwrap(),
+            container.get_elements().last().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_supernode_for_source() {
+        let mut container = create_basic_supernode_container();
+        container.create_nodes().unwrap();
+        assert_eq!(container.supernode_for_source(1), None);
+
+        container.create_super_nodes().unwrap();
+        assert_eq!(container.supernode_for_source(1), Some(4));
+        assert_eq!(container.supernode_for_source(5), None); // Grounded
+        assert_eq!(container.supernode_for_source(2), None);
+        assert_eq!(container.supernode_for_source(10), None);
+    }
+
+    #[test]
+    fn test_validate_with_mode() {
+        // R3 has both sides on the node of V1 and R2
+        let mut container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0., vec![1, 2], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 10., vec![2, 3], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 5., vec![1, 3], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 5., vec![1, 2], vec![1, 2]));
+        assert_eq!(
+            container.validate_with_mode(ValidationMode::Lenient),
+            Ok(Valid)
+        );
+        assert_known_error!(
+            container.validate_with_mode(ValidationMode::Strict),
+            "Element 3 cannot be shorted to id 1"
+        );
+
+        let mut basic: Container = create_basic_container();
+        basic.create_nodes().unwrap();
+        assert_eq!(basic.validate_with_mode(ValidationMode::Strict), Ok(Valid));
+        basic.get_element_by_id(1).unwrap().borrow_mut().value = 1e10;
+        assert_known_error!(
+            basic.validate_with_mode(ValidationMode::Strict),
+            "Element 1 value 10000000000 is outside the typical range of 1e-3 to 1e9"
+        );
+    }
+
+    #[test]
+    fn test_dead_branches() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        assert!(container.dead_branches().is_empty());
+
+        let mut container = create_basic_container();
+        container.add_element_no_id(Element::new(Resistor, 1.0, vec![], vec![1, 2]));
+        container.elements[1].borrow_mut().negative.push(4);
+        container.elements[2].borrow_mut().positive.push(4);
+        container.create_nodes().unwrap();
+        assert_eq!(container.dead_branches(), vec![4]);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let container = create_mna_container();
+        let json: String = container.to_json();
+        let restored = Container::from_json(&json).unwrap();
+
+        assert_eq!(restored.ground, container.ground);
+        assert_eq!(restored.elements.len(), container.elements.len());
+        assert_eq!(restored.to_json(), json);
+
+        assert!(Container::from_json("{\"elements\": []}").is_err());
+        assert!(Container::from_json("not json").is_err());
+
+        // Ids that do not start at 0 are kept along with the connections that use them
+        let shifted: Container = container.clone_with_id_offset(3);
+        let json: String = shifted.to_json();
+        let restored = Container::from_json(&json).unwrap();
+        assert_eq!(restored.ground, 3);
+        assert_eq!(restored.to_json(), json);
+        assert_eq!(restored.get_element_by_id(8).unwrap().borrow().value, 20.0);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        let original: String = container.to_json();
+        let snapshot = container.snapshot();
+
+        container.get_element_by_id(1).unwrap().borrow_mut().value = 5.0;
+        container.add_element_no_id(Element::new(Resistor, 2.0, vec![1], vec![2]));
+        assert_ne!(container.to_json(), original);
+
+        container.restore(snapshot);
+        assert_eq!(container.to_json(), original);
+        assert!(container.nodes().is_empty());
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        let description: String = container.describe();
+
+        for element in container.elements.iter() {
+            assert!(description.contains(&element.pretty_string()));
+        }
+        assert!(description.contains("  R1: 1 Ω (+[3] -[2])"));
+        for node in container.nodes() {
+            let node = node.upgrade().unwrap();
+            let node = node.borrow();
+            assert!(description.contains(&format!(
+                "{} members {}",
+                node.pretty_string(),
+                node.basic_string()
+            )));
+        }
+        assert!(description.ends_with("Status: Valid"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut container = create_mna_container();
+        container.create_nodes().unwrap();
+        let dot = container.to_dot();
+
+        assert!(dot.starts_with("graph circuit {"));
+        assert!(dot.contains("1 [label=\"Node: 1\"];"));
+        for element in container.elements.iter().skip(1) {
+            assert!(dot.contains(&format!("[label=\"{}\"]", element.pretty_string())));
+        }
+        assert!(dot.contains("3 -- 0 [label=\"R1: 2 Ω\"];"));
+    }
+
+    #[test]
+    fn test_to_adjacency_json() {
+        let mut container = create_basic_container();
+        container.create_nodes().unwrap();
+        let graph: serde_json::Value =
+            serde_json::from_str(&container.to_adjacency_json()).unwrap();
+
+        let nodes = graph["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0]["label"], "GND");
+        assert_eq!(nodes[1]["label"], "Node: 1");
+        let edges = graph["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 3);
+        let elements: Vec<u64> = edges
+            .iter()
+            .map(|x| x["element"].as_u64().unwrap())
+            .collect();
+        assert_eq!(elements, vec![1, 2, 3]);
+    }
+
     #[test]
     fn test_get_calculation_nodes() {
         let mut basic: Container = create_basic_container();
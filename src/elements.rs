@@ -1,5 +1,5 @@
-use crate::component::Component;
-use crate::component::Component::Ground;
+use crate::component::Component::{Ground, Resistor, VoltageSrc};
+use crate::component::{Component, WaveformType};
 use crate::container::Container;
 use crate::util::PrettyPrint;
 use crate::validation::Status::Valid;
@@ -27,8 +27,38 @@ pub struct Element {
     pub(crate) class: Component,     //
     pub(crate) positive: Vec<usize>, // Link to other elements
     pub(crate) negative: Vec<usize>, //
+    #[serde(default)]
+    pub(crate) temperature_coefficient: Option<f64>,
+    #[serde(skip)]
+    pub(crate) nominal_value: Option<f64>,
+    #[serde(default)]
+    pub(crate) thermal_voltage: Option<f64>,
+    #[serde(default)]
+    pub(crate) number: Option<usize>, // Shown instead of the id, see `NamingScheme`
+    #[serde(default)]
+    pub(crate) waveform: Option<WaveformType>,
+    #[serde(default = "enabled_default")]
+    pub(crate) enabled: bool, // Disabled elements are left open by the solvers
+    #[serde(default)]
+    pub(crate) position: Option<(f64, f64)>, // Schematic (x, y), ignored by the solvers
 }
 
+fn enabled_default() -> bool {
+    true
+}
+
+/// Temperature (°C) at which element values are specified
+pub const REFERENCE_TEMPERATURE: f64 = 25.0;
+
+/// Largest element value magnitude `validate` accepts, see `Element::validate_magnitude`
+pub const MAX_VALUE: f64 = 1e15;
+
+/// Resistances (Ω) outside this range are rejected by strict validation
+pub const TYPICAL_RESISTANCE: (f64, f64) = (1e-3, 1e9);
+
+/// Diode thermal voltage (V) at room temperature, used when none is set
+pub const THERMAL_VOLTAGE: f64 = 0.025852;
+
 impl Element {
     /// Create a new Element
     ///
@@ -64,11 +94,91 @@ impl Element {
             class,
             positive,
             negative,
+            temperature_coefficient: None,
+            nominal_value: None,
+            thermal_voltage: None,
+            number: None,
+            waveform: None,
+            enabled: true,
+            position: None,
+        }
+    }
+
+    /// Set the linear temperature coefficient (α, per °C) of the element
+    pub fn with_temperature_coefficient(mut self, alpha: f64) -> Element {
+        self.temperature_coefficient = Some(alpha);
+        self
+    }
+
+    /// Set the thermal voltage (Vt) of a diode, the value holds its saturation current (Is)
+    pub fn with_thermal_voltage(mut self, vt: f64) -> Element {
+        self.thermal_voltage = Some(vt);
+        self
+    }
+
+    /// Set the waveform of a source, kept for AC and transient analysis
+    pub fn with_waveform(mut self, waveform: WaveformType) -> Element {
+        self.waveform = Some(waveform);
+        self
+    }
+
+    pub(crate) fn thermal_voltage(&self) -> f64 {
+        self.thermal_voltage.unwrap_or(THERMAL_VOLTAGE)
+    }
+
+    /// Adjust the value to `R0·(1 + α·(T - T0))`, keeping the nominal value for later calls
+    pub(crate) fn apply_temperature(&mut self, temp_c: f64) {
+        if let Some(alpha) = self.temperature_coefficient {
+            let nominal = *self.nominal_value.get_or_insert(self.value);
+            self.value = nominal * (1.0 + alpha * (temp_c - REFERENCE_TEMPERATURE));
+        }
+    }
+
+    /// Reject values that are not finite or larger in magnitude than `max`.
+    pub fn validate_magnitude(&self, max: f64) -> ValidationResult {
+        if !self.value.is_finite() {
+            return Err(Known(format!(
+                "Value must be finite {}",
+                self.pretty_string()
+            )));
+        }
+        if self.value.abs() > max {
+            return Err(Known(format!(
+                "Value exceeds the maximum of {:e} {}",
+                max,
+                self.pretty_string()
+            )));
+        }
+        Ok(Valid)
+    }
+
+    /// Sort and remove duplicates from both connection lists
+    pub fn normalize_connections(&mut self) {
+        for side in [&mut self.positive, &mut self.negative] {
+            side.sort();
+            side.dedup();
+        }
+    }
+
+    /// Conductance (S) of a resistor, 0 for other elements
+    ///
+    /// `validate` rejects resistances of zero or below, so validated elements never divide
+    /// by zero here.
+    pub fn conductance(&self) -> f64 {
+        match self.class {
+            Resistor => 1.0 / self.value,
+            _ => 0.0,
         }
     }
 
-    pub(crate) fn connected_to_ground(&self) -> bool {
-        self.positive.contains(&0) || self.negative.contains(&0)
+    /// Whether either side lists the ground element, its id is `Container::ground`.
+    pub(crate) fn connected_to_ground(&self, ground: usize) -> bool {
+        self.positive.contains(&ground) || self.negative.contains(&ground)
+    }
+
+    /// Number shown after the name, the id unless a naming scheme assigned one
+    pub(crate) fn number(&self) -> usize {
+        self.number.unwrap_or(self.id)
     }
 
     pub(crate) fn set_name(&mut self, name: String) {
@@ -93,7 +203,8 @@ impl Element {
     ) -> Result<Vec<Weak<RefCell<Element>>>, StatusError> {
         let out = Vec::new();
         for id in self.positive.iter() {
-            let element: Weak<RefCell<Element>> = Rc::downgrade(container.get_element_by_id(*id));
+            let element: Weak<RefCell<Element>> =
+                Rc::downgrade(container.get_element_by_id(*id).unwrap());
         }
         Ok(out)
     }
@@ -104,14 +215,14 @@ impl PrettyPrint for Element {
         format!(
             "{}{}: {} {}",
             self.name,
-            self.id,
+            self.number(),
             self.value,
             self.class.unit_string()
         )
     }
 
     fn basic_string(&self) -> String {
-        format!("{}{}", self.name, self.id)
+        format!("{}{}", self.name, self.number())
     }
 }
 
@@ -129,7 +240,7 @@ impl Into<EquationRepr> for Element {
     fn into(self) -> EquationRepr {
         EquationRepr::new_with_latex(
             self.basic_string(),
-            format!("{}_{{{}}}", self.name, self.id),
+            format!("{}_{{{}}}", self.name, self.number()),
             self.value,
         )
     }
@@ -145,7 +256,7 @@ impl EquationMember for Element {
     }
 
     fn latex_string(&self) -> String {
-        format!("{{{}}}_{{{}}}", self.name, self.id)
+        format!("{{{}}}_{{{}}}", self.name, self.number())
     }
 }
 
@@ -172,6 +283,9 @@ impl Validation for Element {
     fn validate(&self) -> ValidationResult {
         match self.class {
             Ground => {
+                if self.positive.is_empty() && self.negative.is_empty() {
+                    return Err(Known("Ground is not connected".to_string()));
+                }
                 if self.positive.len() != 0 && self.negative.len() != 0 {
                     return Err(Known(
                         "Ground element cannot have dual polarity".to_string(),
@@ -184,23 +298,18 @@ impl Validation for Element {
             _ => {
                 // TODO: Check if the element is valid for other components
                 // Resistor, Capacitor, Inductor, VoltageSource, CurrentSource
-                if self.value <= 0.0 {
+                self.validate_magnitude(MAX_VALUE)?;
+                // A 0V source is a plain wire, the solver merges its nodes through the
+                // resulting supernode constraint.
+                let zero_allowed: bool = self.class == VoltageSrc && self.value == 0.0;
+                if self.value <= 0.0 && !zero_allowed {
                     return Err(Known(format!(
                         "Value cannot be zero or negative {}",
                         self.pretty_string()
                     )));
                 }
 
-                // TODO: This should be a simplification not a validation?
-                for x in self.positive.iter() {
-                    if self.negative.contains(x) {
-                        // return Err(Known(format!(
-                        //     "Element {} cannot be shorted to id {}",
-                        //     self.pretty_string(),
-                        //     x
-                        // )));
-                    }
-                }
+                // Shorted elements are only rejected by Container::validate_with_mode(Strict)
             }
         }
         if self.positive.len() == 0 && self.negative.len() == 0 {
@@ -238,15 +347,21 @@ impl Serialize for Element {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Element", 9)?;
+        let mut state = serializer.serialize_struct("Element", 16)?;
         state.serialize_field("name", &self.name)?;
         state.serialize_field("id", &self.id)?;
+        state.serialize_field("number", &self.number)?;
         state.serialize_field("value", &self.value)?;
         state.serialize_field("current", &self.current.value())?;
         state.serialize_field("voltage_drop", &self.voltage_drop)?;
         state.serialize_field("class", &self.class)?;
         state.serialize_field("positive", &self.positive)?;
         state.serialize_field("negative", &self.negative)?;
+        state.serialize_field("temperature_coefficient", &self.temperature_coefficient)?;
+        state.serialize_field("thermal_voltage", &self.thermal_voltage)?;
+        state.serialize_field("waveform", &self.waveform)?;
+        state.serialize_field("enabled", &self.enabled)?;
+        state.serialize_field("position", &self.position)?;
         state.serialize_field("pretty_string", &self.pretty_string())?;
         state.serialize_field("latex_string", &self.latex_string())?;
         state.end()
@@ -260,6 +375,7 @@ mod tests {
     use crate::elements::Element;
     use crate::validation::StatusError::Known;
     use crate::validation::Validation;
+    use serde_json::json;
 
     #[test]
     fn test_new() {
@@ -280,6 +396,59 @@ mod tests {
         assert_eq!(element.negative, Vec::<usize>::new());
     }
 
+    #[test]
+    fn test_position_round_trip() {
+        let json = json!({
+            "id": 1,
+            "value": 2.0,
+            "class": "Resistor",
+            "positive": [0],
+            "negative": [2],
+            "position": [120.5, -40.0]
+        });
+        let element: Element = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(element.position, Some((120.5, -40.0)));
+        let serialized = serde_json::to_value(&element).unwrap();
+        assert_eq!(serialized["position"], json["position"]);
+
+        let element: Element = serde_json::from_value(serialized).unwrap();
+        assert_eq!(element.position, Some((120.5, -40.0)));
+        let element: Element = serde_json::from_value(json!({
+            "id": 1,
+            "value": 2.0,
+            "class": "Resistor",
+            "positive": [0],
+            "negative": [2]
+        }))
+        .unwrap();
+        assert_eq!(element.position, None);
+    }
+
+    #[test]
+    fn test_conductance() {
+        assert_eq!(
+            Element::new(Component::Resistor, 4.0, vec![1], vec![2]).conductance(),
+            0.25
+        );
+        assert_eq!(
+            Element::new(Component::VoltageSrc, 4.0, vec![1], vec![2]).conductance(),
+            0.0
+        );
+        let short = Element::new(Component::Resistor, 0.0, vec![1], vec![2]);
+        assert!(matches!(
+            short.validate(),
+            Err(Known(x)) if x.starts_with("Value cannot be zero or negative")
+        ));
+    }
+
+    #[test]
+    fn test_normalize_connections() {
+        let mut element = Element::new(Component::Resistor, 1.0, vec![3, 2, 3], vec![4, 4]);
+        element.normalize_connections();
+        assert_eq!(element.positive, vec![2, 3]);
+        assert_eq!(element.negative, vec![4]);
+    }
+
     #[test]
     fn test_validate() {
         let mut a = Element::new(Component::Resistor, 1.0, vec![3], vec![2]);
@@ -310,6 +479,13 @@ mod tests {
             class: Component::Ground,
             positive: vec![1],
             negative: vec![2],
+            temperature_coefficient: None,
+            nominal_value: None,
+            thermal_voltage: None,
+            number: None,
+            waveform: None,
+            enabled: true,
+            position: None,
         };
         assert_known_error!(d.validate(), "Ground element cannot have dual polarity");
 
@@ -320,4 +496,33 @@ mod tests {
         let f = Element::new(Component::Resistor, 1.0, vec![], vec![]);
         assert_known_error!(f.validate(), "Element has no connections");
     }
+
+    #[test]
+    fn test_validate_magnitude() {
+        let mut a = Element::new(Component::VoltageSrc, f64::INFINITY, vec![2], vec![3]);
+        a.id = 1;
+        assert_known_error!(a.validate(), "Value must be finite SRC(V)1: inf V");
+
+        a.value = f64::MAX;
+        assert!(a.validate().is_err());
+        a.value = f64::NAN;
+        assert!(a.validate().is_err());
+
+        a.value = 1e15;
+        assert!(a.validate().is_ok());
+        a.value = 1e16;
+        assert!(a.validate().is_err());
+        assert!(a.validate_magnitude(1e20).is_ok());
+    }
+
+    #[test]
+    fn test_validate_zero_value() {
+        let mut a = Element::new(Component::VoltageSrc, 0.0, vec![2], vec![3]);
+        a.id = 1;
+        assert!(a.validate().is_ok());
+
+        let mut b = Element::new(Component::Resistor, 0.0, vec![2], vec![3]);
+        b.id = 1;
+        assert_known_error!(b.validate(), "Value cannot be zero or negative R1: 0 Ω");
+    }
 }
@@ -1,15 +1,19 @@
 use crate::container::Container;
 use crate::elements::Element;
+use crate::solvers::mesh_matrix_solver::MeshMatrixSolver;
+use crate::solvers::mesh_step_solver::MeshStepSolver;
 use crate::solvers::node_matrix_solver::NodeMatrixSolver;
 use crate::solvers::node_step_solver::NodeStepSolver;
-use crate::solvers::solver::{serialize_steps, Solver, Step};
+use crate::solvers::solver::{
+    serialize_steps, solver_capabilities, supported_solvers, Capabilities, Solver, SolverType, Step,
+};
 use crate::util::{
     create_basic_container, create_basic_supermesh_container, create_basic_supernode_container,
     create_mna_container, create_mna_container_2,
 };
 use crate::validation::{StatusError, Validation};
 use serde::{Deserialize, Serialize};
-use serde_wasm_bindgen::from_value;
+use serde_wasm_bindgen::{from_value, to_value};
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
@@ -21,6 +25,21 @@ pub struct ContainerSetup {
     pub elements: Vec<Element>,
 }
 
+/// Outcome of a single circuit solved as part of a batch.
+#[derive(Serialize)]
+pub enum BatchResult {
+    Steps(Vec<Step>),
+    Error(StatusError),
+}
+
+/// Numeric modified nodal analysis system `A·x = z`, rows first.
+#[derive(Serialize)]
+pub struct MnaMatrices {
+    pub a: Vec<Vec<f64>>,
+    pub x: Vec<String>,
+    pub z: Vec<f64>,
+}
+
 /// This can be used as a test to see if the container is being loaded in properly.
 #[wasm_bindgen]
 pub fn load_wasm_container(js: JsValue) -> Result<String, StatusError> {
@@ -63,33 +82,126 @@ pub fn solve(matrix: bool, nodal: bool, container_js: JsValue) -> Result<String,
         return Err(String::from(Known("Failed to parse and deserialize input case".to_string())));
     };
 
-    let mut c: Container = Container::from(setup);
+    let solver_type: SolverType = match (nodal, matrix) {
+        (true, true) => {
+            return Err(String::from(Known(
+                "Matrix solver not implemented for nodal".to_string(),
+            )))
+        }
+        (true, false) => SolverType::NodeStep,
+        (false, true) => SolverType::MeshMatrix,
+        (false, false) => SolverType::MeshStep,
+    };
+
+    serialize_steps(solve_container(Container::from(setup), solver_type)?)
+}
+
+/// Solve a container with the chosen solver, creating the nodes it needs first.
+///
+/// This is the entry behind `solve` for Rust callers that already hold a `Container`. The
+/// mesh solvers walk `Container::fundamental_loops` and do not need Mesh tools.
+pub fn solve_container(
+    mut c: Container,
+    solver_type: SolverType,
+) -> Result<Vec<Step>, StatusError> {
     c.validate()?;
+    c.create_nodes()?;
+    c.create_super_nodes()?;
 
-    return match nodal {
-        true => {
-            c.create_nodes()?;
-            c.create_super_nodes()?;
-            let steps: Vec<Step>;
-            if matrix {
-                return Err(String::from(Known("Matrix solver not implemented for nodal".to_string())));
-                let mut solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
-                steps = solver.solve()?;
-            } else {
-                let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
-                steps = solver.solve()?;
-            }
-            serialize_steps(steps)
+    match solver_type {
+        SolverType::NodeMatrix => {
+            let mut solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.solve()
         }
-        false => {
-            return Err(format!(
-                "{} Solver not implemented for meshes",
-                if matrix { "Matrix" } else { "Step" }
-            ));
-            c.create_meshes();
-            c.create_super_meshes();
+        SolverType::NodeStep => {
+            let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.solve()
         }
+        SolverType::MeshMatrix => {
+            let mut solver: MeshMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.solve()
+        }
+        SolverType::MeshStep => {
+            let mut solver: MeshStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.solve()
+        }
+    }
+}
+
+/// The A, X and Z matrices the node matrix solver builds, for doing the linear algebra elsewhere.
+#[wasm_bindgen]
+pub fn get_mna_matrices(container_js: JsValue) -> Result<JsValue, StatusError> {
+    let setup: ContainerSetup = match from_value(container_js) {
+        Ok(setup) => setup,
+        Err(_) => {
+            return Err(Known(
+                "Failed to parse and deserialize input case".to_string(),
+            ))
+        }
+    };
+    match to_value(&mna_matrices(Container::from(setup))?) {
+        Ok(js) => Ok(js),
+        Err(_) => Err(Known("Error serializing MNA matrices".to_string())),
+    }
+}
+
+pub(crate) fn mna_matrices(mut c: Container) -> Result<MnaMatrices, StatusError> {
+    c.validate()?;
+    c.create_nodes()?;
+    let solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+    let (a, x, z) = solver.numeric_matrices();
+    Ok(MnaMatrices {
+        a: a.row_iter()
+            .map(|row| row.iter().copied().collect())
+            .collect(),
+        x,
+        z: z.iter().copied().collect(),
+    })
+}
+
+/// Solve several containers at once.
+///
+/// Each circuit is solved independently with the node step solver, a failing circuit
+/// is reported in its own result and does not abort the rest of the batch.
+#[wasm_bindgen]
+pub fn solve_batch(containers_js: JsValue) -> Result<String, String> {
+    let setups: Vec<ContainerSetup> = if let Ok(setups) = from_value(containers_js) {
+        setups
+    } else {
+        return Err(String::from(Known(
+            "Failed to parse and deserialize input cases".to_string(),
+        )));
     };
+
+    match serde_json::to_string(&solve_setups(setups)) {
+        Ok(a) => Ok(a),
+        Err(_) => Err("Error serializing batch results".to_string()),
+    }
+}
+
+pub(crate) fn solve_setups(setups: Vec<ContainerSetup>) -> Vec<BatchResult> {
+    setups
+        .into_iter()
+        .map(
+            |setup| match solve_container(Container::from(setup), SolverType::NodeStep) {
+                Ok(steps) => BatchResult::Steps(steps),
+                Err(error) => BatchResult::Error(error),
+            },
+        )
+        .collect()
+}
+
+/// Every available solver with what it can handle, as `[solver, capabilities]` pairs.
+#[wasm_bindgen]
+pub fn get_supported_solvers() -> Result<JsValue, StatusError> {
+    let solvers: Vec<(SolverType, Capabilities)> = supported_solvers()
+        .into_iter()
+        .map(|x| (x.clone(), solver_capabilities(x)))
+        .collect();
+    match to_value(&solvers) {
+        Ok(js) => Ok(js),
+        Err(_) => Err(Known("Error serializing supported solvers".to_string())),
+    }
 }
 
 #[wasm_bindgen]
@@ -128,10 +240,114 @@ impl From<Vec<Element>> for Container {
 
 impl From<ContainerSetup> for Container {
     fn from(setup: ContainerSetup) -> Container {
-        let mut container = Container::new();
-        for element in setup.elements {
-            container.add_element_no_id(element);
+        Container::from_elements(setup.elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::container::Container;
+    use crate::interfaces::{
+        mna_matrices, solve_container, solve_setups, BatchResult, ContainerSetup,
+    };
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::{Solver, SolverType};
+    use crate::util::{create_basic_supermesh_container, create_mna_container};
+    use crate::validation::Status::Valid;
+    use crate::validation::Validation;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn mna_setup() -> ContainerSetup {
+        ContainerSetup {
+            elements: create_mna_container()
+                .get_elements()
+                .iter()
+                .map(|x| x.borrow().clone())
+                .collect(),
         }
-        container
+    }
+
+    #[test]
+    fn test_non_sequential_ids() {
+        // The mna circuit renumbered with ground at 9 and the 20V source at 0, connections
+        // refer to the new ids
+        let ids: [usize; 6] = [9, 10, 7, 3, 12, 0];
+        let mut setup: ContainerSetup = mna_setup();
+        for element in setup.elements.iter_mut() {
+            element.id = ids[element.id];
+            element.positive.iter_mut().for_each(|x| *x = ids[*x]);
+            element.negative.iter_mut().for_each(|x| *x = ids[*x]);
+        }
+
+        let mut container: Container = Container::from(setup);
+        assert_eq!(container.validate(), Ok(Valid));
+        assert_eq!(container.get_element_by_id(12).unwrap().borrow().value, 32.0);
+        assert!(container.get_element_by_id(1).is_none());
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve().unwrap();
+
+        // The 4 Ω resistor lists the source at id 0, it would pass for grounded if ground was
+        // taken as id 0
+        let voltages = solver.node_voltage_map();
+        for (node, expected) in [(1, 20.0), (2, -88.0 / 7.0), (3, 136.0 / 7.0)] {
+            assert!((voltages[&node] - expected).abs() < 1e-9);
+        }
+        let current: f64 = container.borrow().current_through(10).unwrap();
+        assert!((current - 68.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_solve_batch() {
+        let results = solve_setups(vec![
+            mna_setup(),
+            ContainerSetup { elements: vec![] },
+            mna_setup(),
+        ]);
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], BatchResult::Steps(_)));
+        assert!(matches!(results[1], BatchResult::Error(_)));
+        assert!(matches!(results[2], BatchResult::Steps(_)));
+    }
+
+    #[test]
+    fn test_solve_with_mesh_step() {
+        let steps = solve_container(create_mna_container(), SolverType::MeshStep).unwrap();
+        assert_eq!(steps[0].title(), Some("Mesh Equations".to_string()));
+        assert_eq!(steps[0].get_steps().len(), 2);
+
+        let steps = solve_container(create_mna_container(), SolverType::NodeStep).unwrap();
+        assert_ne!(steps[0].title(), Some("Mesh Equations".to_string()));
+
+        assert!(solve_container(create_basic_supermesh_container(), SolverType::MeshStep).is_err());
+    }
+
+    #[test]
+    fn test_solve_container() {
+        let steps = solve_container(create_mna_container(), SolverType::NodeStep).unwrap();
+        assert_eq!(steps.len(), 6);
+        assert_eq!(steps[0].title(), Some("KCL Equations".to_string()));
+
+        assert!(solve_container(Container::new(), SolverType::NodeStep).is_err());
+    }
+
+    #[test]
+    fn test_mna_matrices() {
+        let expected: Vec<Vec<f64>> = vec![
+            vec![0.5, 0.0, 0.0, 1.0, 0.0],
+            vec![0.0, 0.375, -0.25, -1.0, 0.0],
+            vec![0.0, -0.25, 0.25, 0.0, 1.0],
+            vec![1.0, -1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0, 0.0],
+        ];
+
+        let matrices = mna_matrices(create_mna_container()).unwrap();
+        assert_eq!(matrices.a, expected);
+        assert_eq!(matrices.x.len(), 5);
+        assert_eq!(matrices.z, vec![0.0, 0.0, 0.0, 32.0, 20.0]);
     }
 }
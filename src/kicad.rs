@@ -0,0 +1,320 @@
+use crate::component::Component;
+use crate::component::Component::{CurrentSrc, Ground, Resistor, VoltageSrc};
+use crate::elements::Element;
+use crate::validation::StatusError;
+use crate::validation::StatusError::Known;
+
+/// Net names treated as the 0V reference
+const GROUND_NETS: [&str; 3] = ["GND", "/GND", "0"];
+
+/// Parsed s-expression from a KiCad netlist export
+#[derive(Debug, PartialEq)]
+enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Atom(String),
+}
+
+impl SExpr {
+    fn parse(input: &str) -> Result<SExpr, StatusError> {
+        let tokens: Vec<Token> = tokenize(input)?;
+        let mut position: usize = 0;
+        let expr: SExpr = parse_tokens(&tokens, &mut position)?;
+        if position != tokens.len() {
+            return Err(Known("Unexpected content after netlist".to_string()));
+        }
+        Ok(expr)
+    }
+
+    /// Leading atom of a list, `export` for `(export ...)`
+    fn name(&self) -> Option<&str> {
+        match self {
+            SExpr::List(items) => match items.first() {
+                Some(SExpr::Atom(atom)) => Some(atom.as_str()),
+                _ => None,
+            },
+            SExpr::Atom(_) => None,
+        }
+    }
+
+    /// Child lists with the given name
+    fn children(&self, name: &str) -> Vec<&SExpr> {
+        match self {
+            SExpr::List(items) => items.iter().filter(|x| x.name() == Some(name)).collect(),
+            SExpr::Atom(_) => vec![],
+        }
+    }
+
+    /// Value of a `(name value)` child
+    fn field(&self, name: &str) -> Option<&str> {
+        match self.children(name).into_iter().next()? {
+            SExpr::List(items) => match items.get(1) {
+                Some(SExpr::Atom(atom)) => Some(atom.as_str()),
+                _ => None,
+            },
+            SExpr::Atom(_) => None,
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, StatusError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' => {
+                let mut atom: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(x) => atom.push(x),
+                            None => {
+                                return Err(Known("Unterminated string in netlist".to_string()))
+                            }
+                        },
+                        Some(x) => atom.push(x),
+                        None => return Err(Known("Unterminated string in netlist".to_string())),
+                    }
+                }
+                tokens.push(Token::Atom(atom));
+            }
+            c if c.is_whitespace() => continue,
+            c => {
+                let mut atom: String = c.to_string();
+                while let Some(&x) = chars.peek() {
+                    if x.is_whitespace() || x == '(' || x == ')' || x == '"' {
+                        break;
+                    }
+                    atom.push(x);
+                    chars.next();
+                }
+                tokens.push(Token::Atom(atom));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_tokens(tokens: &[Token], position: &mut usize) -> Result<SExpr, StatusError> {
+    match tokens.get(*position) {
+        Some(Token::Open) => {
+            *position += 1;
+            let mut items: Vec<SExpr> = Vec::new();
+            loop {
+                match tokens.get(*position) {
+                    Some(Token::Close) => {
+                        *position += 1;
+                        return Ok(SExpr::List(items));
+                    }
+                    Some(_) => items.push(parse_tokens(tokens, position)?),
+                    None => return Err(Known("Unbalanced parentheses in netlist".to_string())),
+                }
+            }
+        }
+        Some(Token::Atom(atom)) => {
+            *position += 1;
+            Ok(SExpr::Atom(atom.clone()))
+        }
+        Some(Token::Close) => Err(Known("Unbalanced parentheses in netlist".to_string())),
+        None => Err(Known("Empty netlist".to_string())),
+    }
+}
+
+/// Component type from the reference designator, R1 is a Resistor
+fn component_class(reference: &str) -> Result<Component, StatusError> {
+    match reference.chars().next().map(|x| x.to_ascii_uppercase()) {
+        Some('R') => Ok(Resistor),
+        Some('V') => Ok(VoltageSrc),
+        Some('I') => Ok(CurrentSrc),
+        _ => Err(Known(format!("Unsupported component: {}", reference))),
+    }
+}
+
+/// Parse a value field such as `1k`, `4k7`, `10V` or `2meg` into base units
+fn parse_value(value: &str) -> Result<f64, StatusError> {
+    let split: usize = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+
+    let (multiplier, rest): (f64, &str) = if suffix.to_lowercase().starts_with("meg") {
+        (1e6, &suffix[3..])
+    } else {
+        match suffix.chars().next() {
+            Some(c) => {
+                let multiplier: f64 = match c {
+                    'T' => 1e12,
+                    'G' => 1e9,
+                    'M' => 1e6,
+                    'k' | 'K' => 1e3,
+                    'm' => 1e-3,
+                    'u' | 'µ' => 1e-6,
+                    'n' => 1e-9,
+                    'p' => 1e-12,
+                    _ => 1.0, // Unit letters such as V, A or R
+                };
+                (multiplier, &suffix[c.len_utf8()..])
+            }
+            None => (1.0, ""),
+        }
+    };
+
+    // The suffix doubles as the decimal point in values like 4k7
+    let fraction: String = rest.chars().take_while(|x| x.is_ascii_digit()).collect();
+    let number: String = match fraction.is_empty() {
+        true => number.to_string(),
+        false => format!("{}.{}", number, fraction),
+    };
+    match number.parse::<f64>() {
+        Ok(x) => Ok(x * multiplier),
+        Err(_) => Err(Known(format!("Invalid component value: {}", value))),
+    }
+}
+
+/// Parse a KiCad `(export (components ...) (nets ...))` netlist into Elements.
+///
+/// The Ground element comes first, components follow in listed order with ids matching
+/// their index. Pin 1 is the positive side and pin 2 the negative side of a component.
+pub(crate) fn parse_netlist(netlist: &str) -> Result<Vec<Element>, StatusError> {
+    let export: SExpr = SExpr::parse(netlist)?;
+    if export.name() != Some("export") {
+        return Err(Known("Netlist must start with (export ...)".to_string()));
+    }
+
+    let mut references: Vec<String> = vec!["GND".to_string()];
+    let mut elements: Vec<Element> = vec![Element::new(Ground, 0.0, vec![], vec![])];
+    for components in export.children("components") {
+        for comp in components.children("comp") {
+            let reference: &str = match comp.field("ref") {
+                Some(reference) => reference,
+                None => return Err(Known("Component without a ref".to_string())),
+            };
+            let class: Component = component_class(reference)?;
+            let value: f64 = parse_value(comp.field("value").unwrap_or(""))?;
+            let id: usize = elements.len();
+            elements.push(Element::new_full(class, value, vec![], vec![], id));
+            references.push(reference.to_string());
+        }
+    }
+
+    for nets in export.children("nets") {
+        for net in nets.children("net") {
+            let mut pins: Vec<(usize, bool)> = Vec::new(); // (Element ID, positive side)
+            for node in net.children("node") {
+                let reference: &str = node.field("ref").unwrap_or("");
+                let id: usize = match references.iter().position(|x| x == reference) {
+                    Some(id) if id != 0 => id,
+                    _ => return Err(Known(format!("Unknown component in net: {}", reference))),
+                };
+                let positive: bool = match node.field("pin") {
+                    Some("1") => true,
+                    Some("2") => false,
+                    pin => {
+                        return Err(Known(format!(
+                            "Unsupported pin {} on {}",
+                            pin.unwrap_or(""),
+                            reference
+                        )))
+                    }
+                };
+                pins.push((id, positive));
+            }
+            if net
+                .field("name")
+                .map_or(false, |x| GROUND_NETS.contains(&x))
+            {
+                pins.push((0, true));
+            }
+
+            for (id, positive) in pins.iter() {
+                let others: Vec<usize> = pins.iter().map(|x| x.0).filter(|x| x != id).collect();
+                match *positive {
+                    true => elements[*id].positive.extend(others),
+                    false => elements[*id].negative.extend(others),
+                }
+            }
+        }
+    }
+
+    if elements[0].positive.is_empty() {
+        return Err(Known("Netlist has no ground net".to_string()));
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_known_error;
+    use crate::component::Component::{Ground, Resistor, VoltageSrc};
+    use crate::container::Container;
+    use crate::kicad::{parse_netlist, parse_value};
+    use crate::validation::StatusError::Known;
+
+    const NETLIST: &str = r#"(export (version "E")
+  (design (source "divider.kicad_sch") (tool "Eeschema 7.0.0"))
+  (components
+    (comp (ref "V1") (value "5V") (footprint ""))
+    (comp (ref "R1") (value "1k"))
+    (comp (ref "R2") (value "4k7")))
+  (nets
+    (net (code "1") (name "GND")
+      (node (ref "V1") (pin "2"))
+      (node (ref "R2") (pin "2")))
+    (net (code "2") (name "/IN")
+      (node (ref "V1") (pin "1"))
+      (node (ref "R1") (pin "1")))
+    (net (code "3") (name "/OUT")
+      (node (ref "R1") (pin "2"))
+      (node (ref "R2") (pin "1")))))"#;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("10").unwrap(), 10.0);
+        assert_eq!(parse_value("5V").unwrap(), 5.0);
+        assert_eq!(parse_value("1k").unwrap(), 1000.0);
+        assert_eq!(parse_value("4k7").unwrap(), 4700.0);
+        assert_eq!(parse_value("2meg").unwrap(), 2e6);
+        assert_eq!(parse_value("2m").unwrap(), 0.002);
+        assert_known_error!(parse_value("ohms"), "Invalid component value: ohms");
+    }
+
+    #[test]
+    fn test_parse_netlist() {
+        let elements = parse_netlist(NETLIST).unwrap();
+        assert_eq!(elements.len(), 4);
+        assert_eq!(elements[0].class, Ground);
+        assert_eq!(elements[0].positive, vec![1, 3]);
+
+        assert_eq!(elements[1].class, VoltageSrc);
+        assert_eq!(elements[1].value, 5.0);
+        assert_eq!(elements[1].positive, vec![2]);
+        assert_eq!(elements[1].negative, vec![3, 0]);
+
+        assert_eq!(elements[3].class, Resistor);
+        assert_eq!(elements[3].value, 4700.0);
+        assert_eq!(elements[3].positive, vec![2]);
+        assert_eq!(elements[3].negative, vec![1, 0]);
+
+        let mut container = Container::from_kicad_netlist(NETLIST).unwrap();
+        container.create_nodes().unwrap();
+        assert_eq!(container.nodes().len(), 2);
+
+        assert_known_error!(
+            parse_netlist("(export (components (comp (ref \"C1\") (value \"1u\"))))"),
+            "Unsupported component: C1"
+        );
+        assert_known_error!(
+            parse_netlist("(export (components (comp (ref \"R1\") (value \"1\")))"),
+            "Unbalanced parentheses in netlist"
+        );
+    }
+}
@@ -2,6 +2,8 @@ pub mod component;
 pub mod container;
 pub mod elements;
 pub mod interfaces;
+mod kicad;
+mod planarity;
 pub mod solvers;
 pub mod tools;
 pub mod util;
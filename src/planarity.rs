@@ -0,0 +1,343 @@
+use std::collections::{HashSet, VecDeque};
+
+/// Planarity test for an undirected graph given as edges between vertex indices.
+///
+/// Every biconnected block is embedded face by face with the Demoucron, Malgrange and
+/// Pertuiset algorithm, the graph is planar when all of its blocks embed.
+pub(crate) fn is_planar(vertices: usize, edges: &[(usize, usize)]) -> bool {
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); vertices];
+    for (a, b) in edges {
+        if a != b && !adjacency[*a].contains(b) {
+            adjacency[*a].push(*b);
+            adjacency[*b].push(*a);
+        }
+    }
+    biconnected_components(&adjacency)
+        .iter()
+        .all(|block| block_is_planar(block))
+}
+
+/// Part of a graph that is not yet embedded, either a single edge or a connected set of
+/// vertices, along with the embedded vertices it attaches to.
+struct Fragment {
+    attachments: Vec<usize>,
+    inner: Vec<usize>,
+}
+
+/// Tarjan's biconnected components, collected as edge lists
+struct Blocks<'a> {
+    adjacency: &'a [Vec<usize>],
+    discovery: Vec<Option<usize>>,
+    low: Vec<usize>,
+    time: usize,
+    stack: Vec<(usize, usize)>,
+    blocks: Vec<Vec<(usize, usize)>>,
+}
+
+impl Blocks<'_> {
+    fn visit(&mut self, v: usize, parent: Option<usize>) {
+        let adjacency = self.adjacency;
+        self.discovery[v] = Some(self.time);
+        self.low[v] = self.time;
+        self.time += 1;
+
+        for &w in adjacency[v].iter() {
+            match self.discovery[w] {
+                None => {
+                    self.stack.push((v, w));
+                    self.visit(w, Some(v));
+                    self.low[v] = self.low[v].min(self.low[w]);
+                    if self.low[w] >= self.discovery[v].unwrap() {
+                        // v separates everything found below (v, w) into its own block
+                        let mut block: Vec<(usize, usize)> = Vec::new();
+                        while let Some(edge) = self.stack.pop() {
+                            block.push(edge);
+                            if edge == (v, w) {
+                                break;
+                            }
+                        }
+                        self.blocks.push(block);
+                    }
+                }
+                Some(d) if Some(w) != parent && d < self.discovery[v].unwrap() => {
+                    self.stack.push((v, w));
+                    self.low[v] = self.low[v].min(d);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn biconnected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<(usize, usize)>> {
+    let mut state = Blocks {
+        adjacency,
+        discovery: vec![None; adjacency.len()],
+        low: vec![0; adjacency.len()],
+        time: 0,
+        stack: vec![],
+        blocks: vec![],
+    };
+    for v in 0..adjacency.len() {
+        if state.discovery[v].is_none() {
+            state.visit(v, None);
+        }
+    }
+    state.blocks
+}
+
+fn block_is_planar(block: &[(usize, usize)]) -> bool {
+    let mut vertices: Vec<usize> = block.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    vertices.sort();
+    vertices.dedup();
+
+    // The smallest non-planar graphs are K5 and K3,3, dense blocks fail Euler's bound
+    let (n, m) = (vertices.len(), block.len());
+    if n < 5 || m < 9 {
+        return true;
+    }
+    if m > 3 * n - 6 {
+        return false;
+    }
+
+    let index = |v: usize| vertices.binary_search(&v).unwrap();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (a, b) in block {
+        adjacency[index(*a)].push(index(*b));
+        adjacency[index(*b)].push(index(*a));
+    }
+    embeds(&adjacency)
+}
+
+/// Demoucron, Malgrange and Pertuiset embedding of a biconnected graph
+fn embeds(adjacency: &[Vec<usize>]) -> bool {
+    let mut in_h: Vec<bool> = vec![false; adjacency.len()];
+    let mut h_edges: HashSet<(usize, usize)> = HashSet::new();
+
+    let cycle: Vec<usize> = initial_cycle(adjacency);
+    add_path(&cycle, &mut in_h, &mut h_edges);
+    add_path(&[*cycle.last().unwrap(), cycle[0]], &mut in_h, &mut h_edges);
+    let mut faces: Vec<Vec<usize>> = vec![cycle.clone(), cycle];
+
+    loop {
+        let fragments: Vec<Fragment> = fragments(adjacency, &in_h, &h_edges);
+        if fragments.is_empty() {
+            return true;
+        }
+
+        // Fragments with a single admissible face have to go there, others can go anywhere
+        let mut choice: Option<(usize, usize)> = None; // (Fragment, Face)
+        for (i, fragment) in fragments.iter().enumerate() {
+            let admissible: Vec<usize> = faces
+                .iter()
+                .enumerate()
+                .filter(|(_, face)| fragment.attachments.iter().all(|x| face.contains(x)))
+                .map(|(f, _)| f)
+                .collect();
+            match admissible.len() {
+                0 => return false,
+                1 => {
+                    choice = Some((i, admissible[0]));
+                    break;
+                }
+                _ => {
+                    if choice.is_none() {
+                        choice = Some((i, admissible[0]));
+                    }
+                }
+            }
+        }
+        let (i, f) = choice.unwrap();
+        let path: Vec<usize> = fragment_path(adjacency, &in_h, &fragments[i]);
+
+        // The path splits the face in two
+        let face: Vec<usize> = faces.remove(f);
+        let start: usize = face.iter().position(|x| *x == path[0]).unwrap();
+        let end: usize = face.iter().position(|x| x == path.last().unwrap()).unwrap();
+        let walk = |from: usize, to: usize| -> Vec<usize> {
+            let mut out: Vec<usize> = Vec::new();
+            let mut k: usize = from;
+            loop {
+                out.push(face[k]);
+                if k == to {
+                    return out;
+                }
+                k = (k + 1) % face.len();
+            }
+        };
+        let interior: &[usize] = &path[1..path.len() - 1];
+        let mut first: Vec<usize> = walk(start, end);
+        first.extend(interior.iter().rev());
+        let mut second: Vec<usize> = walk(end, start);
+        second.extend(interior.iter());
+        faces.push(first);
+        faces.push(second);
+
+        add_path(&path, &mut in_h, &mut h_edges);
+    }
+}
+
+fn add_path(path: &[usize], in_h: &mut [bool], h_edges: &mut HashSet<(usize, usize)>) {
+    for v in path {
+        in_h[*v] = true;
+    }
+    for pair in path.windows(2) {
+        h_edges.insert((pair[0].min(pair[1]), pair[0].max(pair[1])));
+    }
+}
+
+/// Any cycle through the first edge, found as the shortest path around it
+fn initial_cycle(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let (u, v) = (0, adjacency[0][0]);
+    let mut parent: Vec<Option<usize>> = vec![None; adjacency.len()];
+    let mut queue: VecDeque<usize> = VecDeque::from(vec![v]);
+    while let Some(x) = queue.pop_front() {
+        for &y in adjacency[x].iter() {
+            if (x == v && y == u) || y == v || parent[y].is_some() {
+                continue;
+            }
+            parent[y] = Some(x);
+            if y == u {
+                let mut cycle: Vec<usize> = vec![u];
+                let mut node: usize = u;
+                while let Some(p) = parent[node] {
+                    cycle.push(p);
+                    node = p;
+                }
+                return cycle;
+            }
+            queue.push_back(y);
+        }
+    }
+    vec![u, v]
+}
+
+fn fragments(
+    adjacency: &[Vec<usize>],
+    in_h: &[bool],
+    h_edges: &HashSet<(usize, usize)>,
+) -> Vec<Fragment> {
+    let mut out: Vec<Fragment> = Vec::new();
+    for u in 0..adjacency.len() {
+        for &v in adjacency[u].iter() {
+            if u < v && in_h[u] && in_h[v] && !h_edges.contains(&(u, v)) {
+                out.push(Fragment {
+                    attachments: vec![u, v],
+                    inner: vec![],
+                });
+            }
+        }
+    }
+
+    let mut seen: Vec<bool> = vec![false; adjacency.len()];
+    for start in 0..adjacency.len() {
+        if in_h[start] || seen[start] {
+            continue;
+        }
+        let mut fragment = Fragment {
+            attachments: vec![],
+            inner: vec![],
+        };
+        let mut queue: VecDeque<usize> = VecDeque::from(vec![start]);
+        seen[start] = true;
+        while let Some(x) = queue.pop_front() {
+            fragment.inner.push(x);
+            for &y in adjacency[x].iter() {
+                if in_h[y] {
+                    if !fragment.attachments.contains(&y) {
+                        fragment.attachments.push(y);
+                    }
+                } else if !seen[y] {
+                    seen[y] = true;
+                    queue.push_back(y);
+                }
+            }
+        }
+        out.push(fragment);
+    }
+    out
+}
+
+/// Path through a fragment between two of its attachments
+fn fragment_path(adjacency: &[Vec<usize>], in_h: &[bool], fragment: &Fragment) -> Vec<usize> {
+    let a: usize = fragment.attachments[0];
+    if fragment.inner.is_empty() {
+        return vec![a, fragment.attachments[1]];
+    }
+
+    let mut parent: Vec<Option<usize>> = vec![None; adjacency.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for &c in adjacency[a].iter().filter(|x| fragment.inner.contains(x)) {
+        parent[c] = Some(a);
+        queue.push_back(c);
+    }
+    while let Some(x) = queue.pop_front() {
+        for &y in adjacency[x].iter() {
+            if in_h[y] && y != a {
+                let mut path: Vec<usize> = vec![y, x];
+                let mut node: usize = x;
+                while let Some(p) = parent[node] {
+                    path.push(p);
+                    node = p;
+                }
+                path.reverse();
+                return path;
+            }
+            if !in_h[y] && parent[y].is_none() {
+                parent[y] = Some(x);
+                queue.push_back(y);
+            }
+        }
+    }
+    vec![a]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::planarity::is_planar;
+
+    fn complete(n: usize) -> Vec<(usize, usize)> {
+        (0..n)
+            .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_planar() {
+        // Wheatstone bridge, the source spans the bridge like a fifth arm
+        let bridge = vec![(0, 1), (0, 2), (1, 2), (1, 3), (2, 3), (0, 3)];
+        assert!(is_planar(4, &bridge));
+        assert!(is_planar(4, &complete(4)));
+
+        assert!(!is_planar(5, &complete(5)));
+        let mut k5_minus_edge = complete(5);
+        k5_minus_edge.pop();
+        assert!(is_planar(5, &k5_minus_edge));
+
+        let k33: Vec<(usize, usize)> = (0..3).flat_map(|a| (3..6).map(move |b| (a, b))).collect();
+        assert!(!is_planar(6, &k33));
+
+        // Subdivided K3,3 with a planar tail hanging off a cut vertex
+        let mut subdivided = k33.clone();
+        subdivided.retain(|x| *x != (0, 3));
+        subdivided.extend(vec![(0, 6), (6, 3), (6, 7), (7, 8), (8, 6)]);
+        assert!(!is_planar(9, &subdivided));
+
+        // A cube is planar but dense enough to go through the embedding
+        let cube = vec![
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 0),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 4),
+            (0, 4),
+            (1, 5),
+            (2, 6),
+            (3, 7),
+        ];
+        assert!(is_planar(8, &cube));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::validation::StatusError;
+use crate::validation::StatusError::Known;
+use nalgebra::{DMatrix, DVector};
+use operations::math::EquationMember;
+use std::any::Any;
+use std::panic;
+
+/// Dense linear system `A x = b` used by the node solvers.
+///
+/// Keeps the solvers independent of the matrix library backing them.
+pub trait LinearSystem {
+    /// Inverse of the system matrix.
+    fn invert(&self) -> Result<DMatrix<f64>, StatusError>;
+
+    /// Solve the system for the given right hand side.
+    fn solve_for(&self, rhs: &DVector<f64>) -> Result<DVector<f64>, StatusError> {
+        Ok(self.invert()? * rhs)
+    }
+}
+
+impl LinearSystem for DMatrix<f64> {
+    fn invert(&self) -> Result<DMatrix<f64>, StatusError> {
+        if !self.is_square() {
+            return Err(Known(format!(
+                "Matrix is not square: {}",
+                self.equation_repr()
+            )));
+        }
+
+        let inverse_result: Result<Option<DMatrix<f64>>, Box<dyn Any + Send>> =
+            panic::catch_unwind(|| self.clone().try_inverse());
+        match inverse_result {
+            Ok(Some(inverse)) => Ok(inverse),
+            _ => Err(Known(format!(
+                "Unable to invert matrix: {}",
+                self.equation_repr()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::linear_system::LinearSystem;
+    use nalgebra::{DMatrix, DVector};
+
+    #[test]
+    fn test_solve_for() {
+        let a: DMatrix<f64> = DMatrix::from_row_slice(2, 2, &[2.0, 0.0, 0.0, 4.0]);
+        let x = a.solve_for(&DVector::from_vec(vec![2.0, 2.0])).unwrap();
+        assert_eq!(x, DVector::from_vec(vec![1.0, 0.5]));
+
+        let singular: DMatrix<f64> = DMatrix::from_row_slice(2, 2, &[1.0, 1.0, 1.0, 1.0]);
+        assert!(singular.invert().is_err());
+        assert!(DMatrix::<f64>::zeros(2, 3).invert().is_err());
+    }
+}
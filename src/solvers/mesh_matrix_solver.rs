@@ -1,22 +1,276 @@
+use crate::component::Component::{CurrentSrc, Resistor, VoltageSrc};
 use crate::container::Container;
-use crate::solvers::solver::{Solver, Step};
+use crate::elements::Element;
+use crate::solvers::linear_system::LinearSystem;
+use crate::solvers::solver::{Solver, Step, SubStep};
 use crate::validation::StatusError;
+use crate::validation::StatusError::Known;
+use nalgebra::{DMatrix, DVector};
+use operations::math::{EquationMember, EquationRepr};
+use operations::prelude::{Negate, Operation, Sum, Text, Value, Variable};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-// TODO MeshMatrixSolver
-#[allow(dead_code)]
 pub struct MeshMatrixSolver {
     container: Rc<RefCell<Container>>,
+    loops: Vec<Vec<(usize, f64)>>, // (Element ID, direction along the mesh) for each mesh
+    mesh_currents: DVector<f64>,   // Unrounded, the step output shows two decimals
 }
 
 impl Solver for MeshMatrixSolver {
     fn new(container: Rc<RefCell<Container>>) -> Self {
-        MeshMatrixSolver { container }
+        MeshMatrixSolver {
+            container,
+            loops: vec![],
+            mesh_currents: DVector::zeros(0),
+        }
     }
 
+    /// Returns the matrix equation Z * I = V for the mesh currents.
     fn solve(&mut self) -> Result<Vec<Step>, StatusError> {
-        todo!()
+        let container = self.container.borrow();
+        let loops: Vec<Vec<(usize, f64)>> = mesh_loops(&container)?;
+        self.loops = loops.clone();
+        let z_matrix: DMatrix<Operation> = form_impedance_matrix(&container, &loops);
+        let v_vector: DVector<Operation> = form_source_vector(&container, &loops);
+        let i_vector: DVector<Operation> =
+            DVector::from_iterator(loops.len(), (0..loops.len()).map(mesh_current_variable));
+
+        let inverse: DMatrix<f64> = match z_matrix.map(|x| x.value()).invert() {
+            Ok(a) => a,
+            Err(_) => {
+                return Err(Known(format!(
+                    "Matrix is not invertible!\n{}\n",
+                    z_matrix.latex_string()
+                )));
+            }
+        };
+        self.mesh_currents = inverse.clone() * v_vector.map(|x| x.value());
+        let mut result: DVector<f64> = self.mesh_currents.clone();
+        result
+            .iter_mut()
+            .for_each(|x| *x = (*x * 100.).round() / 100.);
+
+        Ok(vec![Step {
+            title: Some("Mesh Matrix Solver".to_string()),
+            description: Some("Form matrices".to_string()),
+            sub_steps: vec![
+                SubStep {
+                    description: Some("Z Matrix".to_string()),
+                    result: None,
+                    operations: vec![Variable(Rc::new(z_matrix.clone()))],
+                },
+                SubStep {
+                    description: Some("V Matrix".to_string()),
+                    result: None,
+                    operations: vec![Variable(Rc::new(v_vector.clone()))],
+                },
+                SubStep {
+                    description: Some("I Matrix".to_string()),
+                    result: None,
+                    operations: vec![Variable(Rc::new(i_vector.clone()))],
+                },
+                SubStep {
+                    description: Some("Inverse Z Matrix".to_string()),
+                    result: None,
+                    operations: vec![Variable(Rc::new(inverse))],
+                },
+                SubStep {
+                    description: Some("Final Equation".to_string()),
+                    result: None,
+                    operations: vec![Text(format!(
+                        "{} = {}^{{-1}} * {}",
+                        i_vector.equation_repr(),
+                        z_matrix.equation_repr(),
+                        v_vector.equation_repr()
+                    ))],
+                },
+            ],
+            result: Some(Text(format!(
+                "${} = {}$",
+                i_vector.equation_repr(),
+                result.equation_repr()
+            ))),
+        }])
+    }
+}
+
+impl MeshMatrixSolver {
+    /// Current through every resistor, in `get_all_node_pairs` order, from the mesh currents.
+    pub fn branch_currents(&self) -> DVector<f64> {
+        mesh_branch_currents(&self.container.borrow(), &self.loops, &self.mesh_currents)
+    }
+}
+
+/// Loops to write mesh equations for, rejecting circuits mesh analysis can not handle yet.
+pub(crate) fn mesh_loops(container: &Container) -> Result<Vec<Vec<(usize, f64)>>, StatusError> {
+    if container
+        .get_elements()
+        .iter()
+        .any(|x| x.borrow().class == CurrentSrc)
+    {
+        return Err(Known(
+            "Mesh analysis does not support current sources".to_string(),
+        ));
+    }
+    if !container.is_planar() {
+        return Err(Known("Mesh analysis requires a planar circuit".to_string()));
+    }
+    let loops: Vec<Vec<(usize, f64)>> = container.fundamental_loops()?;
+    if loops.is_empty() {
+        return Err(Known("No meshes to solve".to_string()));
+    }
+    Ok(loops)
+}
+
+/// Unknown current circulating in mesh k
+pub(crate) fn mesh_current_variable(k: usize) -> Operation {
+    Variable(Rc::new(EquationRepr::new_with_latex(
+        format!("I{}", k + 1),
+        format!("I_{{{}}}", k + 1),
+        0.0,
+    )))
+}
+
+/// Sum of the mesh currents through each resistor, oriented as its node pair.
+pub(crate) fn mesh_branch_currents(
+    container: &Container,
+    loops: &[Vec<(usize, f64)>],
+    mesh_currents: &DVector<f64>,
+) -> DVector<f64> {
+    let resistors: Vec<usize> = container
+        .get_all_node_pairs()
+        .iter()
+        .filter(|(_, _, element)| element.borrow().class == Resistor)
+        .map(|(_, _, element)| element.borrow().id)
+        .collect();
+
+    DVector::from_iterator(
+        resistors.len(),
+        resistors.iter().map(|id| {
+            loops
+                .iter()
+                .zip(mesh_currents.iter())
+                .filter_map(|(terms, current)| {
+                    terms.iter().find(|x| x.0 == *id).map(|x| x.1 * current)
+                })
+                .sum::<f64>()
+        }),
+    )
+}
+
+/// Mesh impedance matrix, entry (k, l) sums the resistors shared by meshes k and l.
+///
+/// Resistors walked in opposite directions by the two meshes are negated.
+pub fn form_impedance_matrix(
+    container: &Container,
+    loops: &[Vec<(usize, f64)>],
+) -> DMatrix<Operation> {
+    let n: usize = loops.len();
+    let mut matrix: DMatrix<Operation> = DMatrix::zeros(n, n);
+    for k in 0..n {
+        for l in 0..n {
+            let mut set: Vec<Operation> = Vec::new();
+            for (id, d_k) in loops[k].iter() {
+                let element = container.get_element_by_id(*id).unwrap().borrow().clone();
+                if element.class != Resistor {
+                    continue;
+                }
+                if let Some((_, d_l)) = loops[l].iter().find(|x| x.0 == *id) {
+                    let term: Operation = Variable(Rc::new(element));
+                    if d_k * d_l > 0.0 {
+                        set.push(term);
+                    } else {
+                        set.push(Negate(Some(Box::new(term))));
+                    }
+                }
+            }
+            matrix[(k, l)] = Sum(set);
+        }
+    }
+    matrix
+}
+
+/// Net source voltage rise around each mesh.
+///
+/// The loop directions walk node pairs, so a source whose positive terminal is the second
+/// node of its pair is walked from its negative side.
+pub fn form_source_vector(
+    container: &Container,
+    loops: &[Vec<(usize, f64)>],
+) -> DVector<Operation> {
+    let pairs: Vec<(usize, usize, Rc<RefCell<Element>>)> = container.get_all_node_pairs();
+    DVector::from_iterator(
+        loops.len(),
+        loops.iter().map(|terms| {
+            let set: Vec<Operation> = terms
+                .iter()
+                .filter_map(|(id, d)| {
+                    let element = container.get_element_by_id(*id).unwrap().borrow().clone();
+                    if element.class != VoltageSrc {
+                        return None;
+                    }
+                    let forward: bool = pairs.iter().any(|(node1, _, x)| {
+                        x.borrow().id == *id
+                            && container.terminal_node(&element, &element.positive) == Some(*node1)
+                    });
+                    // Walking from the positive node to the negative node is a drop
+                    match (*d > 0.0) == forward {
+                        true => Some(Negate(Some(Box::new(Value(element.value))))),
+                        false => Some(Value(element.value)),
+                    }
+                })
+                .collect();
+            Sum(set)
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::mesh_matrix_solver::{
+        form_impedance_matrix, form_source_vector, MeshMatrixSolver,
+    };
+    use crate::solvers::solver::Solver;
+    use crate::util::{create_basic_supermesh_container, create_mna_container};
+    use nalgebra::DVector;
+    use operations::prelude::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_mesh_matrices() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let loops = c.fundamental_loops().unwrap();
+
+        let z = form_impedance_matrix(&c, &loops);
+        let expected = vec![vec!["R2 + R3", "-R3"], vec!["-R3", "R1 + R3"]];
+        for i in 0..2 {
+            for j in 0..2 {
+                assert_eq!(expected[i][j], z[(i, j)].equation_repr());
+            }
+        }
+        assert_eq!(z[(0, 1)].value(), -8.0);
+
+        let v = form_source_vector(&c, &loops);
+        assert_eq!(v[0].value(), 20.0);
+        assert_eq!(v[1].value(), 32.0);
+    }
+
+    #[test]
+    fn test_mesh_matrix_solver() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: MeshMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+        assert!(solver.solve().is_ok());
+        let expected: DVector<f64> = DVector::from_vec(vec![68.0 / 7.0, 57.0 / 7.0, -11.0 / 7.0]);
+        assert!((solver.branch_currents() - expected).abs().max() < 1e-9);
+
+        let mut c = create_basic_supermesh_container();
+        c.create_nodes().unwrap();
+        let mut solver: MeshMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+        assert!(solver.solve().is_err());
     }
 }
 
@@ -1,21 +1,216 @@
+use crate::component::Component::Resistor;
 use crate::container::Container;
-use crate::solvers::solver::{Solver, Step};
+use crate::elements::Element;
+use crate::solvers::linear_system::LinearSystem;
+use crate::solvers::mesh_matrix_solver::{
+    form_impedance_matrix, form_source_vector, mesh_branch_currents, mesh_current_variable,
+    mesh_loops,
+};
+use crate::solvers::solver::{Solver, Step, SubStep};
+use crate::util::PrettyPrint;
 use crate::validation::StatusError;
+use nalgebra::{DMatrix, DVector};
+use operations::math::EquationMember;
+use operations::prelude::{Display, Equal, Multiply, Negate, Operation, Sum, Value, Variable};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-// TODO MeshStepSolver
-#[allow(dead_code)]
 pub struct MeshStepSolver {
     container: Rc<RefCell<Container>>,
+    loops: Vec<Vec<(usize, f64)>>, // (Element ID, direction along the mesh) for each mesh
+    impedance_matrix: DMatrix<Operation>,
+    source_vector: DVector<Operation>,
+    mesh_currents: DVector<f64>, // This is the result of matrix manipulation
 }
 
 impl Solver for MeshStepSolver {
     fn new(container: Rc<RefCell<Container>>) -> Self {
-        MeshStepSolver { container }
+        MeshStepSolver {
+            container,
+            loops: vec![],
+            impedance_matrix: DMatrix::zeros(0, 0),
+            source_vector: DVector::zeros(0),
+            mesh_currents: DVector::zeros(0),
+        }
     }
 
+    /// Returns the steps to solve the circuit with mesh currents.
     fn solve(&mut self) -> Result<Vec<Step>, StatusError> {
-        todo!()
+        // SETUP and CALCULATIONS
+        self.setup_mesh_equations()?;
+        self.solve_mesh_currents()?;
+
+        // FORMATTING and OUTPUT
+        let mut steps: Vec<Step> = Vec::new();
+        steps.push(self.display_mesh_equations());
+        steps.push(self.display_mesh_matrix());
+        steps.push(self.display_currents());
+        Ok(steps)
+    }
+}
+
+impl MeshStepSolver {
+    fn setup_mesh_equations(&mut self) -> Result<(), StatusError> {
+        let container = self.container.borrow();
+        self.loops = mesh_loops(&container)?;
+        self.impedance_matrix = form_impedance_matrix(&container, &self.loops);
+        self.source_vector = form_source_vector(&container, &self.loops);
+        Ok(())
+    }
+
+    fn solve_mesh_currents(&mut self) -> Result<(), StatusError> {
+        let impedance: DMatrix<f64> = self.impedance_matrix.map(|x| x.value());
+        let sources: DVector<f64> = self.source_vector.map(|x| x.value());
+        self.mesh_currents = impedance.solve_for(&sources)?;
+        Ok(())
+    }
+
+    /// KVL around every mesh, the drop over each resistor uses every mesh current through it.
+    fn display_mesh_equations(&self) -> Step {
+        let container = self.container.borrow();
+        let sub_steps: Vec<SubStep> = self
+            .loops
+            .iter()
+            .enumerate()
+            .map(|(k, terms)| {
+                let drops: Vec<Operation> = terms
+                    .iter()
+                    .filter_map(|(id, d_k)| {
+                        let element: Element =
+                            container.get_element_by_id(*id).unwrap().borrow().clone();
+                        if element.class != Resistor {
+                            return None;
+                        }
+                        let currents: Vec<Operation> = self
+                            .loops
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(l, other)| {
+                                let (_, d_l) = other.iter().find(|x| x.0 == *id)?;
+                                match d_k * d_l > 0.0 {
+                                    true => Some(mesh_current_variable(l)),
+                                    false => Some(Negate(Some(Box::new(mesh_current_variable(l))))),
+                                }
+                            })
+                            .collect();
+                        Some(Multiply(vec![Variable(Rc::new(element)), Sum(currents)]))
+                    })
+                    .collect();
+                let members: Vec<String> = terms
+                    .iter()
+                    .map(|(id, _)| container.get_element_by_id(*id).unwrap().basic_string())
+                    .collect();
+
+                SubStep {
+                    description: Some(format!("Mesh {}: {}", k + 1, members.join(", "))),
+                    result: None,
+                    operations: vec![Equal(
+                        Some(Box::new(Sum(drops))),
+                        Some(Box::new(self.source_vector[k].clone())),
+                    )],
+                }
+            })
+            .collect();
+
+        Step {
+            title: Some("Mesh Equations".to_string()),
+            description: Some(
+                "Sum the voltage drops around each mesh and equate them to the source voltages."
+                    .to_string(),
+            ),
+            result: None,
+            sub_steps,
+        }
+    }
+
+    fn display_mesh_matrix(&self) -> Step {
+        let currents: DVector<Operation> = DVector::from_iterator(
+            self.loops.len(),
+            (0..self.loops.len()).map(mesh_current_variable),
+        );
+
+        Step {
+            title: Some("Mesh Matrix".to_string()),
+            description: None,
+            result: Some(Equal(
+                Some(Box::new(Multiply(vec![
+                    Display(Rc::new(self.impedance_matrix.clone())),
+                    Display(Rc::new(currents.clone())),
+                ]))),
+                Some(Box::new(Display(Rc::new(self.source_vector.clone())))),
+            )),
+            sub_steps: vec![SubStep {
+                description: Some(
+                    "Invert the mesh matrix to solve for the mesh currents.".to_string(),
+                ),
+                result: Some(Equal(
+                    Some(Box::new(Display(Rc::new(currents)))),
+                    Some(Box::new(Display(Rc::new(self.mesh_currents.clone())))),
+                )),
+                operations: vec![],
+            }],
+        }
+    }
+
+    fn display_currents(&self) -> Step {
+        let container = self.container.borrow();
+        let i_values: Vec<Operation> = container
+            .get_all_node_pairs()
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .zip(self.branch_currents().iter())
+            .map(|((_, _, element), current)| {
+                let mut i_element: Element = element.borrow().clone();
+                i_element.set_name("i".to_string());
+                Equal(
+                    Some(Box::new(Variable(Rc::new(i_element)))),
+                    Some(Box::new(Value(*current))),
+                )
+            })
+            .collect();
+
+        Step {
+            title: Some("Currents".to_string()),
+            description: Some("Add the mesh currents flowing through each resistor.".to_string()),
+            result: None,
+            sub_steps: vec![SubStep {
+                description: None,
+                result: None,
+                operations: i_values,
+            }],
+        }
+    }
+
+    /// Current through every resistor, in `get_all_node_pairs` order, from the mesh currents.
+    pub fn branch_currents(&self) -> DVector<f64> {
+        mesh_branch_currents(&self.container.borrow(), &self.loops, &self.mesh_currents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::mesh_step_solver::MeshStepSolver;
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::Solver;
+    use crate::util::create_mna_container;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_mesh_currents() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: MeshStepSolver = Solver::new(Rc::new(RefCell::new(c.clone())));
+        let steps = solver.solve().unwrap();
+        assert_eq!(steps.len(), 3);
+        assert_eq!(solver.loops.len(), 2);
+        assert!((solver.mesh_currents[0] - 57.0 / 7.0).abs() < 1e-9);
+        assert!((solver.mesh_currents[1] - 68.0 / 7.0).abs() < 1e-9);
+
+        c.create_super_nodes();
+        let mut node_solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        node_solver.solve().unwrap();
+        let difference = solver.branch_currents() - node_solver.branch_currents();
+        assert!(difference.iter().all(|x| x.abs() < 1e-9));
     }
 }
@@ -1,5 +1,7 @@
+pub mod linear_system;
 pub mod mesh_matrix_solver;
 pub mod mesh_step_solver;
 pub mod node_matrix_solver;
 pub mod node_step_solver;
+pub mod nonlinear_node_solver;
 pub mod solver;
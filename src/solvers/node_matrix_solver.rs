@@ -1,128 +1,424 @@
-use crate::component::Component::{CurrentSrc, Resistor, VoltageSrc};
+use crate::component::Component::{CurrentSrc, Resistor};
 use crate::container::Container;
 use crate::elements::Element;
+use crate::solvers::linear_system::LinearSystem;
 use crate::solvers::solver::{Solver, Step, SubStep};
 use crate::util::PrettyPrint;
 use crate::validation::StatusError::Known;
 use crate::validation::{StatusError, Validation};
 use nalgebra::{DMatrix, DVector};
 use operations::math::{EquationMember, EquationRepr};
-use operations::prelude::{Divide, Negate, Operation, Sum, Text, Value, Variable};
+use operations::prelude::{Divide, Equal, Negate, Operation, Sum, Text, Value, Variable};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+/// Extra conditions for partially specified problems, see `NodeMatrixSolver::with_constraints`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    /// Current through a resistor from its positive to its negative side
+    KnownCurrent { element_id: usize, value: f64 },
+    /// Voltage source whose value is solved for instead of given
+    UnknownSource { element_id: usize },
+}
+
 pub struct NodeMatrixSolver {
+    container: Rc<RefCell<Container>>,
     a_matrix: DMatrix<Operation>,
     x_matrix: DVector<Operation>,
     z_matrix: DVector<Operation>,
+    source_ids: Vec<usize>, // Voltage sources in the order of the J entries of x
+    solution: DVector<f64>, // Solved x vector
+    constraints: Vec<Constraint>,
+    show_elimination: bool, // Row reduce A in the steps instead of inverting it directly
 }
 
 impl Solver for NodeMatrixSolver {
+    /// Builds the matrices from the nodes of the container, run `create_nodes` first.
     fn new(container: Rc<RefCell<Container>>) -> NodeMatrixSolver {
-        container.borrow_mut().create_nodes();
         let n = container.borrow().nodes().len();
-        let m = container // Source Count
+        let m = container.borrow().get_voltage_sources().len(); // Source Count
+
+        let source_ids: Vec<usize> = container
             .borrow()
-            .get_elements()
+            .get_voltage_sources()
             .iter()
-            .fold(0, |acc: usize, x: &Rc<RefCell<Element>>| {
-                match x.borrow().class {
-                    VoltageSrc => acc + 1,
-                    _ => acc,
-                }
-            });
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect();
+
+        // Without nodes there is nothing to assemble, solve reports it instead
+        if n == 0 {
+            return NodeMatrixSolver {
+                a_matrix: DMatrix::zeros(0, 0),
+                x_matrix: DVector::zeros(0),
+                z_matrix: DVector::zeros(0),
+                container,
+                source_ids,
+                solution: DVector::zeros(0),
+                constraints: vec![],
+                show_elimination: false,
+            };
+        }
 
         // https://lpsa.swarthmore.edu/Systems/Electrical/mna/MNA3.html#B_matrix
         NodeMatrixSolver {
             a_matrix: form_a_matrix(container.clone(), n, m),
             x_matrix: form_x_vector(container.clone()),
             z_matrix: form_z_vector(container.clone()),
+            container,
+            source_ids,
+            solution: DVector::zeros(0),
+            constraints: vec![],
+            show_elimination: false,
         }
     }
 
     /// Returns a string that represents the matrix equation to solve the circuit.
     fn solve(&mut self) -> Result<Vec<Step>, StatusError> {
+        if self.a_matrix.is_empty() {
+            return Err(Known(
+                "Circuit has no nodes, run create_nodes on a non-trivial circuit".to_string(),
+            ));
+        }
         let mut steps: Vec<Step> = Vec::new();
 
-        let inverse_result: Option<DMatrix<f64>> = DMatrix::from_iterator(
-            self.a_matrix.nrows(),
-            self.a_matrix.ncols(),
-            self.a_matrix.iter().map(|x| x.value()),
-        )
-        .try_inverse();
-
-        let inverse: DMatrix<f64>;
-        match inverse_result {
-            Some(a) => {
-                inverse = a;
-            }
-            None => {
+        let (a_matrix, z_vector): (DMatrix<f64>, DVector<f64>) = self.constrained_system()?;
+        let mut elimination: Vec<SubStep> = Vec::new();
+        let inverted: Result<DMatrix<f64>, StatusError> = match self.show_elimination {
+            true => gauss_jordan(&a_matrix).map(|(inverse, sub_steps)| {
+                elimination = sub_steps;
+                inverse
+            }),
+            false => a_matrix.invert(),
+        };
+        let inverse: DMatrix<f64> = match inverted {
+            Ok(a) => a,
+            Err(_) => {
                 return Err(Known(format!(
                     "Matrix is not invertible!\nThis might have something to do with sizing.\n{}\n",
                     self.a_matrix.latex_string()
                 )));
             }
-        }
-
-        let z_vector: DVector<f64> = self
-            .z_matrix
-            .iter()
-            .map(|x| x.value())
-            .collect::<Vec<f64>>()
-            .into();
+        };
 
         let mut result = inverse.clone() * z_vector;
+        self.solution = result.clone();
 
         result
             .iter_mut()
             .for_each(|x| *x = (*x * 100.).round() / 100.);
 
+        let mut sub_steps: Vec<SubStep> = vec![
+            SubStep {
+                description: Some("A Matrix".to_string()),
+                result: None,
+                operations: vec![Variable(Rc::new(self.a_matrix.clone()))],
+            },
+            SubStep {
+                description: Some("Z Matrix".to_string()),
+                result: None,
+                operations: vec![Variable(Rc::new(self.z_matrix.clone()))],
+            },
+            SubStep {
+                result: None,
+                description: Some("X Matrix".to_string()),
+                operations: vec![Variable(Rc::new(self.x_matrix.clone()))],
+            },
+        ];
+        sub_steps.append(&mut elimination);
+        sub_steps.push(SubStep {
+            description: Some("Inverse A Matrix".to_string()),
+            result: None,
+            operations: vec![Variable(Rc::new(inverse.clone()))],
+        });
+        sub_steps.push(SubStep {
+            description: Some("Final Equation".to_string()),
+            result: None,
+            operations: vec![Text(format!(
+                "{} = {}^{{-1}} * {}",
+                self.x_matrix.equation_repr(),
+                self.a_matrix.equation_repr(),
+                self.z_matrix.equation_repr()
+            ))],
+        });
+
         steps.push(Step {
             title: Some("Node Matrix Solver".to_string()),
             description: Some("Form matrices".to_string()),
-            sub_steps: vec![
-                SubStep {
-                    description: Some("A Matrix".to_string()),
-                    result: None,
-                    operations: vec![Variable(Rc::new(self.a_matrix.clone()))],
-                },
-                SubStep {
-                    description: Some("Z Matrix".to_string()),
-                    result: None,
-                    operations: vec![Variable(Rc::new(self.z_matrix.clone()))],
-                },
-                SubStep {
-                    result: None,
-                    description: Some("X Matrix".to_string()),
-                    operations: vec![Variable(Rc::new(self.x_matrix.clone()))],
-                },
-                SubStep {
-                    description: Some("Inverse A Matrix".to_string()),
-                    result: None,
-                    operations: vec![Variable(Rc::new(inverse.clone()))],
-                },
-                SubStep {
-                    description: Some("Final Equation".to_string()),
-                    result: None,
-                    operations: vec![Text(format!(
-                        "{} = {}^{{-1}} * {}",
-                        self.x_matrix.equation_repr(),
-                        self.a_matrix.equation_repr(),
-                        self.z_matrix.equation_repr()
-                    ))],
-                },
-            ],
+            sub_steps,
             result: Some(Text(format!(
                 "${} = {}$",
                 self.x_matrix.equation_repr(),
                 result.equation_repr()
             ))),
         });
+        steps.push(self.display_source_currents());
 
         Ok(steps)
     }
 }
 
+impl NodeMatrixSolver {
+    /// Solve with extra constraints, each known current needs a source with an unknown value.
+    ///
+    /// A known current adds a row holding Ohm's law for its resistor, an unknown source
+    /// moves its value to the left hand side as a new entry of x after the source currents.
+    pub fn with_constraints(mut self, constraints: Vec<Constraint>) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Show the Gauss-Jordan row reduction of A, one sub step per pivot, off by default.
+    pub fn show_elimination(mut self, show: bool) -> Self {
+        self.show_elimination = show;
+        self
+    }
+
+    /// Numeric A and z with the labels of x, the system `solve` inverts without constraints.
+    pub fn numeric_matrices(&self) -> (DMatrix<f64>, Vec<String>, DVector<f64>) {
+        (
+            self.a_matrix.map(|x| x.value()),
+            self.x_matrix.iter().map(|x| x.equation_repr()).collect(),
+            self.z_matrix.map(|x| x.value()),
+        )
+    }
+
+    /// Current through each voltage source, see `source_currents` for the sign.
+    fn display_source_currents(&self) -> Step {
+        let container = self.container.borrow();
+        let sub_steps: Vec<SubStep> = self
+            .source_currents()
+            .iter()
+            .map(|(id, current)| {
+                let element = container.get_element_by_id(*id).unwrap();
+                let mut i_element: Element = element.borrow().clone();
+                i_element.set_name("i".to_string());
+                let action: &str = match *current > 0.0 {
+                    true => "absorbs",
+                    false => "delivers",
+                };
+                SubStep {
+                    description: Some(format!(
+                        "{} {} {} A",
+                        element.basic_string(),
+                        action,
+                        (current.abs() * 100.).round() / 100.
+                    )),
+                    result: None,
+                    operations: vec![Equal(
+                        Some(Box::new(Variable(Rc::new(i_element)))),
+                        Some(Box::new(Value(*current))),
+                    )],
+                }
+            })
+            .collect();
+
+        Step {
+            title: Some("Source Currents".to_string()),
+            description: Some(
+                "Current into the positive terminal of each source, negative when it delivers."
+                    .to_string(),
+            ),
+            result: None,
+            sub_steps,
+        }
+    }
+
+    fn known_currents(&self) -> Vec<(usize, f64)> {
+        self.constraints
+            .iter()
+            .filter_map(|x| match x {
+                Constraint::KnownCurrent { element_id, value } => Some((*element_id, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn unknown_sources(&self) -> Vec<usize> {
+        self.constraints
+            .iter()
+            .filter_map(|x| match x {
+                Constraint::UnknownSource { element_id } => Some(*element_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Numeric A matrix and z vector with the constraint rows and columns added.
+    fn constrained_system(&self) -> Result<(DMatrix<f64>, DVector<f64>), StatusError> {
+        let a_matrix: DMatrix<f64> = self.a_matrix.map(|x| x.value());
+        let z_vector: DVector<f64> = self.z_matrix.map(|x| x.value());
+        let known: Vec<(usize, f64)> = self.known_currents();
+        let unknown: Vec<usize> = self.unknown_sources();
+        if known.len() != unknown.len() {
+            return Err(Known(
+                "Each known current needs a source with an unknown value".to_string(),
+            ));
+        }
+        if known.is_empty() {
+            return Ok((a_matrix, z_vector));
+        }
+
+        let size: usize = a_matrix.nrows();
+        let mut a: DMatrix<f64> = DMatrix::zeros(size + known.len(), size + known.len());
+        a.view_mut((0, 0), (size, size)).copy_from(&a_matrix);
+        let mut z: DVector<f64> = DVector::zeros(size + known.len());
+        z.rows_mut(0, size).copy_from(&z_vector);
+
+        // Node rows and columns run in reverse, see form_g_matrix
+        let container = self.container.borrow();
+        let nodes: Vec<usize> = container
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect();
+        let n: usize = nodes.len();
+        let column = |node: usize| nodes.iter().position(|x| *x == node).map(|i| n - i - 1);
+
+        for (k, (element_id, value)) in known.iter().enumerate() {
+            let element: Element = match container.get_element_by_id(*element_id) {
+                Some(element) if element.borrow().class == Resistor => element.borrow().clone(),
+                _ => {
+                    return Err(Known(format!(
+                        "Known current needs a resistor: {}",
+                        element_id
+                    )))
+                }
+            };
+            let conductance: f64 = element.conductance();
+            let positive: Option<usize> = container.terminal_node(&element, &element.positive);
+            let negative: Option<usize> = container.terminal_node(&element, &element.negative);
+            if let Some(i) = positive.and_then(column) {
+                a[(size + k, i)] += conductance;
+            }
+            if let Some(i) = negative.and_then(column) {
+                a[(size + k, i)] -= conductance;
+            }
+            z[size + k] = *value;
+        }
+
+        for (k, element_id) in unknown.iter().enumerate() {
+            let j: usize = match self.source_ids.iter().position(|x| x == element_id) {
+                Some(j) => j,
+                None => {
+                    return Err(Known(format!(
+                        "Element {} is not a voltage source",
+                        element_id
+                    )))
+                }
+            };
+            // V+ - V- - E = 0
+            a[(n + j, size + k)] = -1.0;
+            z[n + j] = 0.0;
+        }
+
+        Ok((a, z))
+    }
+
+    /// (Element ID, current) for every voltage source from the solved x vector.
+    ///
+    /// Current is positive flowing into the positive terminal, so a source delivering power
+    /// reads negative. Empty until the solver has run.
+    pub fn source_currents(&self) -> Vec<(usize, f64)> {
+        let n: usize = self
+            .solution
+            .len()
+            .saturating_sub(self.source_ids.len() + self.unknown_sources().len());
+        self.source_ids
+            .iter()
+            .zip(self.solution.iter().skip(n))
+            .map(|(id, current)| (*id, *current))
+            .collect()
+    }
+
+    /// Solved node voltages in the order of `Container::nodes`, empty until the solver has run.
+    ///
+    /// The node rows of A run in reverse, so x holds the last node first.
+    pub fn node_voltages(&self) -> DVector<f64> {
+        let n: usize = self
+            .solution
+            .len()
+            .saturating_sub(self.source_ids.len() + self.unknown_sources().len());
+        DVector::from_iterator(n, (0..n).rev().map(|k| self.solution[k]))
+    }
+
+    /// (Element ID, value) for every source marked `Constraint::UnknownSource`, once solved.
+    pub fn unknown_source_values(&self) -> Vec<(usize, f64)> {
+        let unknown: Vec<usize> = self.unknown_sources();
+        let n: usize = self.solution.len().saturating_sub(unknown.len());
+        unknown
+            .into_iter()
+            .zip(self.solution.iter().skip(n))
+            .map(|(id, value)| (id, *value))
+            .collect()
+    }
+}
+
+/// Invert a matrix by row reducing `[A | I]`, recording the augmented matrix after each pivot.
+///
+/// Rows are swapped to put the largest remaining entry on the pivot.
+fn gauss_jordan(matrix: &DMatrix<f64>) -> Result<(DMatrix<f64>, Vec<SubStep>), StatusError> {
+    let n: usize = matrix.nrows();
+    if !matrix.is_square() {
+        return Err(Known(format!(
+            "Matrix is not square: {}",
+            matrix.equation_repr()
+        )));
+    }
+
+    let mut augmented: DMatrix<f64> = DMatrix::zeros(n, 2 * n);
+    augmented.view_mut((0, 0), (n, n)).copy_from(matrix);
+    augmented
+        .view_mut((0, n), (n, n))
+        .copy_from(&DMatrix::identity(n, n));
+
+    let mut sub_steps: Vec<SubStep> = Vec::new();
+    for k in 0..n {
+        let pivot: usize = (k..n)
+            .max_by(|a, b| {
+                augmented[(*a, k)]
+                    .abs()
+                    .total_cmp(&augmented[(*b, k)].abs())
+            })
+            .unwrap();
+        if augmented[(pivot, k)].abs() < 1e-12 {
+            return Err(Known(format!(
+                "Unable to invert matrix: {}",
+                matrix.equation_repr()
+            )));
+        }
+        augmented.swap_rows(k, pivot);
+
+        let scale: f64 = augmented[(k, k)];
+        augmented.row_mut(k).iter_mut().for_each(|x| *x /= scale);
+        for row in (0..n).filter(|x| *x != k) {
+            let factor: f64 = augmented[(row, k)];
+            if factor != 0.0 {
+                let pivot_row = augmented.row(k).clone_owned();
+                augmented.row_mut(row).axpy(-factor, &pivot_row, 1.0);
+            }
+        }
+
+        let swap: String = match pivot != k {
+            true => format!("swap $R_{{{}}}$ and $R_{{{}}}$, ", k + 1, pivot + 1),
+            false => "".to_string(),
+        };
+        sub_steps.push(SubStep {
+            description: Some(format!(
+                "Pivot {}: {}divide $R_{{{}}}$ by {} and clear column {}",
+                k + 1,
+                swap,
+                k + 1,
+                scale,
+                k + 1
+            )),
+            result: None,
+            operations: vec![Variable(Rc::new(augmented.clone()))],
+        });
+    }
+
+    Ok((augmented.columns(n, n).clone_owned(), sub_steps))
+}
+
 fn form_a_matrix(container: Rc<RefCell<Container>>, n: usize, m: usize) -> DMatrix<Operation> {
     let mut a_matrix: DMatrix<Operation> = DMatrix::<Operation>::zeros(n + m, n + m);
 
@@ -206,25 +502,16 @@ fn form_g_matrix(container: Rc<RefCell<Container>>, n: usize) -> DMatrix<Operati
 
 pub fn form_b_matrix(container: Rc<RefCell<Container>>, n: usize, m: usize) -> DMatrix<Operation> {
     let mut matrix: DMatrix<Operation> = DMatrix::zeros(n, m);
-
-    for (i, tool) in container.borrow().nodes().iter().enumerate() {
-        for (j, element) in container.borrow().get_voltage_sources().iter().enumerate() {
-            if tool
-                .upgrade()
-                .unwrap()
-                .borrow()
-                .contains(element.upgrade().unwrap())
-            {
-                if element.upgrade().unwrap().borrow().positive.contains(
-                    &tool.upgrade().unwrap().borrow().members[0]
-                        .upgrade()
-                        .unwrap()
-                        .id(),
-                ) {
-                    matrix[(n - i - 1, j)] = Value(-1.0);
-                } else {
-                    matrix[(n - i - 1, j)] = Value(1.0);
-                }
+    let container = container.borrow();
+
+    // +1 on the positive node and -1 on the negative node of each source, ground drops out
+    for (j, source) in container.get_voltage_sources().iter().enumerate() {
+        let source = source.upgrade().unwrap();
+        let source = source.borrow();
+        for (side, sign) in [(&source.positive, 1.0), (&source.negative, -1.0)] {
+            let node: Option<usize> = container.terminal_node(&source, side);
+            if let Some(i) = node.and_then(|x| container.node_index(x)) {
+                matrix[(n - i - 1, j)] = Value(sign);
             }
         }
     }
@@ -251,13 +538,25 @@ fn form_z_vector(container: Rc<RefCell<Container>>) -> DVector<Operation> {
     // I Matrix
     // The balance of current flowing in the node.
     container.borrow().nodes().iter().for_each(|tool| {
+        let tool = tool.upgrade().unwrap();
+        let id: usize = tool.borrow().id;
         let mut set: Vec<Operation> = Vec::new();
-        for element in &tool.upgrade().unwrap().borrow().members {
+        for element in &tool.borrow().members {
             let element = element.upgrade().unwrap();
             if element.borrow().class != CurrentSrc {
                 continue;
             }
-            set.push(Value(element.borrow().value));
+            // Current leaves through the positive side
+            let element = element.borrow();
+            if container
+                .borrow()
+                .terminal_node(&element, &element.positive)
+                == Some(id)
+            {
+                set.push(Value(-element.value));
+            } else {
+                set.push(Value(element.value));
+            }
         }
         if set.len() == 0 {
             z_vec.push(Value(0.0));
@@ -303,15 +602,49 @@ fn form_x_vector(container: Rc<RefCell<Container>>) -> DVector<Operation> {
 
 #[cfg(test)]
 mod tests {
+    use crate::assert_known_error;
+    use crate::component::Component::{CurrentSrc, Ground, Resistor};
+    use crate::container::Container;
+    use crate::elements::Element;
     use crate::solvers::node_matrix_solver::{
-        form_b_matrix, form_c_matrix, form_d_matrix, form_g_matrix, NodeMatrixSolver,
+        form_b_matrix, form_c_matrix, form_d_matrix, form_g_matrix, form_z_vector, gauss_jordan,
+        Constraint, NodeMatrixSolver,
     };
     use crate::solvers::solver::Solver;
+    use crate::tools::ToolType::SuperNode;
     use crate::util::{create_mna_container, create_mna_container_2};
+    use nalgebra::DMatrix;
     use operations::prelude::*;
     use std::cell::RefCell;
     use std::rc::Rc;
 
+    #[test]
+    fn test_no_nodes() {
+        let mut c = Container::new();
+        c.add_element_no_id(Element::new(Ground, 0., vec![], vec![]));
+        let mut solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+        assert_known_error!(
+            solver.solve(),
+            "Circuit has no nodes, run create_nodes on a non-trivial circuit"
+        );
+    }
+
+    #[test]
+    fn test_new_keeps_tools() {
+        // Building the solver leaves the caller's supernodes and labels alone
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        c.label_node(1, "VOUT").unwrap();
+        let container = Rc::new(RefCell::new(c));
+        let _solver: NodeMatrixSolver = Solver::new(container.clone());
+        assert_eq!(container.borrow().get_tools(SuperNode).len(), 1);
+        assert_eq!(
+            container.borrow().get_tool_by_id(0).borrow().label,
+            Some("VOUT".to_string())
+        );
+    }
+
     #[test]
     fn test_node_solver() {
         let mut c = create_mna_container();
@@ -326,13 +659,138 @@ mod tests {
         assert!(steps.is_ok());
     }
 
+    #[test]
+    fn test_display_source_currents() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let steps = solver.solve().unwrap();
+        let step = steps.last().unwrap();
+        assert_eq!(step.title, Some("Source Currents".to_string()));
+
+        let labels: Vec<String> = step
+            .sub_steps
+            .iter()
+            .map(|x| x.description.clone().unwrap())
+            .collect();
+        assert_eq!(labels.len(), 2);
+        for (label, (name, (_, current))) in labels
+            .iter()
+            .zip(["V4", "V5"].iter().zip(solver.source_currents()))
+        {
+            let action: &str = if current > 0.0 { "absorbs" } else { "delivers" };
+            assert!(
+                label.starts_with(&format!("{} {} ", name, action)),
+                "{}",
+                label
+            );
+        }
+    }
+
+    #[test]
+    fn test_show_elimination() {
+        let matrix: DMatrix<f64> = DMatrix::from_row_slice(2, 2, &[0., 2., 4., 1.]);
+        let (inverse, sub_steps) = gauss_jordan(&matrix).unwrap();
+        assert_eq!(sub_steps.len(), 2);
+        assert!(
+            (inverse * matrix - DMatrix::<f64>::identity(2, 2))
+                .abs()
+                .max()
+                < 1e-12
+        );
+        assert!(gauss_jordan(&DMatrix::from_element(2, 2, 1.0)).is_err());
+
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let container = Rc::new(RefCell::new(c));
+        let mut plain: NodeMatrixSolver = Solver::new(container.clone());
+        let plain_steps = plain.solve().unwrap();
+        let solver: NodeMatrixSolver = Solver::new(container);
+        let mut solver: NodeMatrixSolver = solver.show_elimination(true);
+        let steps = solver.solve().unwrap();
+        assert_eq!(steps[0].sub_steps.len(), plain_steps[0].sub_steps.len() + 5);
+        assert!(
+            (solver.solution.clone() - plain.solution.clone())
+                .abs()
+                .max()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_source_currents() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c)));
+        assert!(solver.source_currents().is_empty());
+
+        solver.solve().unwrap();
+        let currents = solver.source_currents();
+        assert_eq!(currents.len(), 2);
+        assert_eq!(currents[0].0, 4);
+        assert!((currents[0].1 + 68.0 / 7.0).abs() < 1e-9);
+        assert_eq!(currents[1].0, 5);
+        assert!((currents[1].1 + 57.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_known_current() {
+        // 6A down through R3 instead of the -11/7 A the 32V source gives
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: NodeMatrixSolver = NodeMatrixSolver::new(Rc::new(RefCell::new(c)))
+            .with_constraints(vec![
+                Constraint::KnownCurrent {
+                    element_id: 3,
+                    value: 6.0,
+                },
+                Constraint::UnknownSource { element_id: 4 },
+            ]);
+        solver.solve().unwrap();
+
+        // Node rows run in reverse, x is [N3, N2, N1, J4, J5, E4]
+        assert_eq!(solver.solution.len(), 6);
+        assert!((solver.solution[1] - 48.0).abs() < 1e-9);
+        assert!((solver.solution[2] - 20.0).abs() < 1e-9);
+        assert!((solver.solution[0] + 26.0).abs() < 1e-9);
+        let values = solver.unknown_source_values();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, 4);
+        assert!((values[0].1 + 74.0).abs() < 1e-9);
+        assert!((solver.source_currents()[0].1 - 13.0).abs() < 1e-9);
+
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        let mut solver: NodeMatrixSolver = NodeMatrixSolver::new(Rc::new(RefCell::new(c)))
+            .with_constraints(vec![Constraint::KnownCurrent {
+                element_id: 3,
+                value: 6.0,
+            }]);
+        assert!(solver.solve().is_err());
+    }
+
+    #[test]
+    fn test_z_vector_current_direction() {
+        let z_entry = |positive: Vec<usize>, negative: Vec<usize>| {
+            let mut c = Container::new();
+            c.add_element_no_id(Element::new(Ground, 0., vec![1, 2], vec![]));
+            c.add_element_no_id(Element::new(CurrentSrc, 1.5, positive, negative));
+            c.add_element_no_id(Element::new(Resistor, 2., vec![1], vec![0]));
+            c.create_nodes().unwrap();
+            form_z_vector(Rc::new(RefCell::new(c)))[0].value()
+        };
+
+        assert_eq!(z_entry(vec![2], vec![0]), -1.5);
+        assert_eq!(z_entry(vec![0], vec![2]), 1.5);
+    }
+
     #[test]
     fn test_a_matrix() {
         let expected = vec![
-            vec!["1/R1", "", "", "-1", "0"],
-            vec!["", "1/R2 + 1/R3", "-1/R2", "1", "0"],
+            vec!["1/R1", "", "", "1", "0"],
+            vec!["", "1/R2 + 1/R3", "-1/R2", "-1", "0"],
             vec!["", "-1/R2", "1/R2", "0", "1"],
-            vec!["-1", "1", "0", "0", "0"],
+            vec!["1", "-1", "0", "0", "0"],
             vec!["0", "0", "1", "0", "0"],
         ];
 
@@ -340,14 +798,14 @@ mod tests {
         c.create_nodes().unwrap();
         let solver: NodeMatrixSolver = Solver::new(Rc::new(RefCell::new(c.clone())));
 
-        assert_eq!(2., c.get_element_by_id(1).clone().borrow().value);
+        assert_eq!(2., c.get_element_by_id(1).unwrap().clone().borrow().value);
         assert_eq!(1. / 2., solver.a_matrix[(0, 0)].value());
         assert_eq!(
             solver.a_matrix[(0, 0)].value(),
             Divide(
                 Some(Box::new(Value(1.0))),
                 Some(Box::new(Variable(Rc::new(
-                    c.get_element_by_id(1).borrow().clone()
+                    c.get_element_by_id(1).unwrap().borrow().clone()
                 ))))
             )
             .value()
@@ -359,9 +817,9 @@ mod tests {
         }
 
         let expected = vec![
-            vec!["1/R2 + 1/R3", "-1/R2", "-1"],
-            vec!["-1/R2", "1/R1 + 1/R2", "1"],
-            vec!["-1", "1", "0"],
+            vec!["1/R2 + 1/R3", "-1/R2", "1"],
+            vec!["-1/R2", "1/R1 + 1/R2", "-1"],
+            vec!["1", "-1", "0"],
         ];
 
         let mut c = create_mna_container_2();
@@ -398,7 +856,7 @@ mod tests {
 
     #[test]
     fn test_b_matrix() {
-        let expected = vec![vec!["-1", "0"], vec!["1", "0"], vec!["0", "1"]];
+        let expected = vec![vec!["1", "0"], vec!["-1", "0"], vec!["0", "1"]];
 
         let mut c = create_mna_container();
         c.create_nodes().unwrap();
@@ -415,7 +873,7 @@ mod tests {
 
     #[test]
     fn test_c_matrix() {
-        let expected = vec![vec!["-1", "1", "0"], vec!["0", "0", "1"]];
+        let expected = vec![vec!["1", "-1", "0"], vec!["0", "0", "1"]];
 
         let mut c = create_mna_container();
         c.create_nodes().unwrap();
@@ -1,9 +1,11 @@
-use crate::component::Component::{Resistor, VoltageSrc};
+use crate::component::Component::{CurrentSrc, Resistor, VoltageSrc};
 use crate::container::Container;
 use crate::elements::Element;
-use crate::solvers::solver::{Solver, Step, SubStep};
+use crate::solvers::linear_system::LinearSystem;
+use crate::solvers::solver::{Locale, SolutionRow, Solver, Step, SubStep};
 use crate::tools::Tool;
 use crate::tools::ToolType::{Node, SuperNode};
+use crate::util::PrettyPrint;
 use crate::validation::StatusError::Known;
 use crate::validation::{StatusError, Validation};
 use nalgebra::{DMatrix, DVector};
@@ -13,30 +15,39 @@ use operations::operations::Operation;
 use operations::prelude::{
     Display, Divide, Equal, Multiply, Negate, Power, Sum, Text, Value, Variable,
 };
-use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::Deref;
-use std::panic;
 use std::rc::{Rc, Weak};
 
 pub struct NodeStepSolver {
     pub(crate) container: Rc<RefCell<Container>>,
-    sources: Vec<SourceConnection>,          // Voltage sources
-    current_values: Vec<(usize, Operation)>, // (Element ID, Equation for current form nodes)
+    sources: Vec<SourceConnection>,              // Voltage sources
+    current_values: Vec<(usize, Operation)>,     // (Element ID, Equation for current form nodes)
+    raw_current_values: Vec<(usize, Operation)>, // current_values before simplification
+    show_unsimplified: bool,
+    show_system: bool,
+    significant_figures: usize, // Used by summary_string
+    scale_currents: bool,       // Show currents in A, mA or µA
+    locale: Locale,
     node_pairs: Vec<(usize, usize, Rc<RefCell<Element>>)>, // Each element is attached to a pair of nodes.
     node_coefficients: Vec<Operation>, // Coefficients of the node summation for the matrix
     node_voltages: DVector<f64>,       // This is the result of matrix manipulation
     connection_matrix: DMatrix<f64>,   // This is the base matrix for manipulation
+    row_labels: Vec<String>,           // What each row of the connection matrix stands for
     node_combination_steps: Vec<Operation>,
     matrix_evaluation: Operation, // Simple operation holding the matrix multiplication display.
     kcl_operations: Vec<Operation>,
     inverse: DMatrix<f64>,
+    reference_offset: f64, // Voltage removed from every node to move the reference off ground.
+    targets: Option<Vec<usize>>, // Nodes the formatted results are limited to, all when None
 }
 
 #[derive(Debug)]
 struct SourceConnection {
     matrix: DVector<f64>,
     voltage: f64,
+    name: String, // Source the row comes from
 }
 
 impl Solver for NodeStepSolver {
@@ -49,14 +60,23 @@ impl Solver for NodeStepSolver {
             container,
             sources: vec![],
             current_values: vec![],
+            raw_current_values: vec![],
+            show_unsimplified: false,
+            show_system: true,
+            significant_figures: 3,
+            scale_currents: false,
+            locale: Locale::English,
             node_pairs,
             node_coefficients: vec![],
             node_voltages: DVector::zeros(0),
             connection_matrix: DMatrix::zeros(0, 0),
+            row_labels: vec![],
             node_combination_steps: vec![],
             matrix_evaluation: Text("".to_string()),
             kcl_operations: vec![],
             inverse: DMatrix::zeros(0, 0),
+            reference_offset: 0.0,
+            targets: None,
         };
 
         out
@@ -66,15 +86,23 @@ impl Solver for NodeStepSolver {
     ///
     /// This Handles the formatting of the data into what the frontend requires.
     fn solve(&mut self) -> Result<Vec<Step>, StatusError> {
+        if self.node_pairs.is_empty() {
+            return Err(Known("Call create_nodes before solving".to_string()));
+        }
+
         // SETUP and CALCULATIONS
         self.setup_connections()?;
         self.setup_node_equations()?;
         self.setup_node_coefficients()?;
         self.solve_node_voltages()?;
+        self.write_back_currents();
 
         // FORMATTING and OUTPUT
         let mut steps: Vec<Step> = Vec::new();
         steps.push(self.display_base_kcl_equations()?);
+        if self.show_system {
+            steps.push(self.display_system_equations());
+        }
         steps.push(self.display_connection_matrix()?);
         steps.push(self.display_solved_matrix()?);
         steps.push(self.display_currents()?);
@@ -84,6 +112,76 @@ impl Solver for NodeStepSolver {
 }
 
 impl NodeStepSolver {
+    /// Solve for the node voltages only, none of the steps are formatted.
+    ///
+    /// Meant for benchmarking large circuits, currents are not written back.
+    pub fn solve_fast(&mut self) -> Result<DVector<f64>, StatusError> {
+        if self.node_pairs.is_empty() {
+            return Err(Known("Call create_nodes before solving".to_string()));
+        }
+        self.setup_connections()?;
+        self.setup_node_equations()?;
+        self.setup_node_coefficients()?;
+        self.solve_node_voltages()?;
+        Ok(self.node_voltages.clone())
+    }
+
+    /// Solve the whole circuit but only format the results for the given nodes.
+    ///
+    /// The node voltages are limited to the requested nodes and the currents to the
+    /// elements attached to them, the KCL and matrix steps are still shown in full.
+    pub fn solve_for_nodes(&mut self, node_ids: &[usize]) -> Result<Vec<Step>, StatusError> {
+        let nodes: Vec<usize> = self
+            .container
+            .borrow()
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect();
+        if let Some(id) = node_ids.iter().find(|x| !nodes.contains(x)) {
+            return Err(Known(format!("Node {} does not exist", id)));
+        }
+
+        self.targets = Some(node_ids.to_vec());
+        let steps = self.solve();
+        self.targets = None;
+        steps
+    }
+
+    /// Whether a node pair touches one of the requested nodes.
+    fn is_target(&self, node1: usize, node2: usize) -> bool {
+        match &self.targets {
+            None => true,
+            Some(targets) => targets.contains(&node1) || targets.contains(&node2),
+        }
+    }
+
+    /// Whether an element is attached to one of the requested nodes.
+    fn is_target_element(&self, id: usize) -> bool {
+        self.node_pairs
+            .iter()
+            .find(|(_, _, element)| element.borrow().id == id)
+            .map_or(true, |(node1, node2, _)| self.is_target(*node1, *node2))
+    }
+
+    /// Language of the step titles and fixed descriptions, English by default.
+    pub fn locale(&mut self, locale: Locale) -> &mut Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Also show the KCL terms as assembled, ahead of their simplified form.
+    pub fn show_unsimplified(&mut self, show: bool) -> &mut Self {
+        self.show_unsimplified = show;
+        self
+    }
+
+    /// Write out the node equations as a scalar system ahead of the matrix step, on by default.
+    pub fn show_system(&mut self, show: bool) -> &mut Self {
+        self.show_system = show;
+        self
+    }
+
     /// Node Pairs
     fn setup_connections(&mut self) -> Result<(), String> {
         let vec_size: usize = match self
@@ -95,35 +193,36 @@ impl NodeStepSolver {
             Some(a) => a.0,
         };
 
-        self.node_pairs
+        let sources: Vec<SourceConnection> = self
+            .node_pairs
             .iter()
             .filter(|(_, _, element)| element.borrow().class == VoltageSrc)
-            .for_each(|(node1, node2, src)| {
+            .map(|(node1, node2, src)| {
                 let mut voltage_connections: DVector<f64> = DVector::zeros(vec_size);
-                match (node1, node2) {
-                    (0, a) | (a, 0) => {
-                        voltage_connections.get_mut(a - 1).map(|x| *x = 1.0);
-                    }
-                    (a, b) => {
-                        voltage_connections
-                            .get_mut::<usize>(a - 1)
-                            .map(|x: &mut f64| *x = 1.0);
-                        voltage_connections
-                            .get_mut::<usize>(b - 1)
-                            .map(|x: &mut f64| *x = -1.0);
-                    }
+                // V+ - V- = E, ground drops out
+                let (positive, negative) = self.source_terminals(*node1, *node2, &src.borrow());
+                if let Some(a) = self.node_index(positive) {
+                    voltage_connections.get_mut(a).map(|x| *x = 1.0);
                 }
-                self.sources.push(SourceConnection {
+                if let Some(b) = self.node_index(negative) {
+                    voltage_connections.get_mut(b).map(|x| *x = -1.0);
+                }
+                SourceConnection {
                     matrix: voltage_connections,
                     voltage: src.borrow().value(),
-                });
-            });
+                    name: src.basic_string(),
+                }
+            })
+            .collect();
+        self.sources.extend(sources);
 
         Ok(())
     }
 
     fn solve_node_voltages(&mut self) -> Result<(), StatusError> {
         let mut source_voltages: DVector<f64> = DVector::zeros(self.sources.len() + 1);
+        let injection: f64 = self.supernode_injection();
+        source_voltages[0] = injection;
 
         self.sources.iter().enumerate().for_each(|(i, x)| {
             source_voltages.get_mut(i + 1).map(|y| *y = x.voltage);
@@ -148,6 +247,31 @@ impl NodeStepSolver {
             });
         });
 
+        self.row_labels = match injection == 0.0 {
+            true => vec!["Row 1: KCL, the currents leaving the nodes sum to zero".to_string()],
+            false => vec![format!(
+                "Row 1: KCL, the currents leaving the nodes sum to the {} A current sources inject",
+                injection
+            )],
+        };
+        self.row_labels
+            .extend(self.sources.iter().enumerate().map(|(i, x)| {
+                let terms: Vec<String> = x
+                    .matrix
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, y)| **y != 0.0)
+                    .map(|(j, y)| format!("{}N{}", if *y < 0.0 { "-" } else { "+" }, j + 1))
+                    .collect();
+                format!(
+                    "Row {}: {} fixes {} = {} V",
+                    i + 2,
+                    x.name,
+                    terms.join(" ").trim_start_matches('+'),
+                    x.voltage
+                )
+            }));
+
         if self.connection_matrix.len() == 0 {
             return Err(Known("No nodes to solve".to_string()));
         }
@@ -158,27 +282,10 @@ impl NodeStepSolver {
             )));
         }
 
-        let inverse_result: Result<Option<DMatrix<f64>>, Box<dyn Any + Send>> =
-            panic::catch_unwind(|| self.connection_matrix.clone().try_inverse());
-
-        let inverse: DMatrix<f64>;
-        if let Err(_) = inverse_result {
-            return Err(Known(format!(
-                "Unable to invert matrix: {}",
-                self.connection_matrix.equation_repr()
-            )));
-        } else if let Ok(None) = inverse_result {
-            return Err(Known(format!(
-                "Unable to invert matrix: {}",
-                self.connection_matrix.equation_repr()
-            )));
-        } else {
-            inverse = inverse_result.unwrap().unwrap();
-        }
+        let inverse: DMatrix<f64> = self.connection_matrix.invert()?;
 
         self.inverse = inverse.clone();
-        let result_matrix = inverse * source_voltages.clone();
-        self.node_voltages = result_matrix.clone();
+        let mut result_matrix = inverse * source_voltages.clone();
 
         self.matrix_evaluation = Display(Rc::new(Equal(
             Some(Box::new(Multiply(vec![
@@ -191,6 +298,17 @@ impl NodeStepSolver {
             Some(Box::new(Display(Rc::new(result_matrix.clone())))),
         )));
 
+        // Shift everything so the chosen reference node reads 0V.
+        let reference: Option<usize> = self.container.borrow().reference_node();
+        if let Some(offset) = reference
+            .and_then(|x| self.node_index(x))
+            .and_then(|x| result_matrix.get(x).copied())
+        {
+            self.reference_offset = offset;
+            result_matrix.add_scalar_mut(-offset);
+        }
+        self.node_voltages = result_matrix.clone();
+
         // Propagate the values of the nodes back into the container / solver.
         let results: Vec<f64> = result_matrix
             .iter()
@@ -204,62 +322,69 @@ impl NodeStepSolver {
             .for_each(|(i, x)| {
                 x.upgrade().unwrap().borrow_mut().set_value(results[i]);
             });
+        self.container
+            .borrow_mut()
+            .set_ground_voltage(-self.reference_offset);
 
         Ok(())
     }
 
     fn setup_node_equations(&mut self) -> Result<(), String> {
         // Form the basic equation for each resistor
-        assert_ne!(self.node_pairs.len(), 0);
-        self.node_pairs
+        if self.node_pairs.is_empty() {
+            return Err("Call create_nodes before solving".to_string());
+        }
+        let group: Vec<usize> = self.source_groups(self.container.borrow().nodes().len());
+        let resistors = self
+            .node_pairs
             .iter()
-            .filter(|(_, _, element)| element.borrow().class == Resistor)
-            .for_each(|(node1, node2, element)| {
-                let mut tools: Vec<Operation> = Vec::new();
-                let mut id_1 = *node1;
-                let mut id_2 = *node2;
-
-                if *node1 != 0 {
-                    id_1 -= 1;
-                    tools.push(Variable(Rc::new(
-                        self.container
-                            .borrow()
-                            .get_tool_by_id(id_1)
-                            .borrow()
-                            .clone(),
-                    )));
-                }
-                if *node2 != 0 {
-                    id_2 -= 1;
-                    tools.push(Negate(Some(Box::new(Variable(Rc::new(
-                        self.container
-                            .borrow()
-                            .get_tool_by_id(id_2)
-                            .borrow()
-                            .clone(),
-                    ))))));
-                }
-
-                let value: f64 = element.borrow().value().clone();
-                element.borrow_mut().set_current(Divide(
-                    Some(Box::new(Sum(tools.clone()).simplify().unwrap())),
-                    Some(Box::new(Value(value))),
-                ));
-
-                self.current_values.push((
-                    element.id(),
-                    Divide(
-                        Some(Box::new(Sum(tools).simplify().unwrap())),
-                        Some(Box::new(Variable(Rc::new(element.borrow().clone())))),
-                    ),
-                ));
+            .filter(|(_, _, element)| element.borrow().class == Resistor);
+        for (node1, node2, element) in resistors {
+            let tools: Vec<Operation> = self.node_difference(*node1, *node2);
+
+            let value: f64 = element.borrow().value().clone();
+            element.borrow_mut().set_current(Divide(
+                Some(Box::new(Sum(tools.clone()).simplify().unwrap())),
+                Some(Box::new(Value(value))),
+            ));
 
-                self.node_combination_steps.push(Negate(Some(Box::new(
-                    self.current_values.last().unwrap().1.clone(),
-                ))));
-            });
+            self.raw_current_values.push((
+                element.id(),
+                Divide(
+                    Some(Box::new(Sum(tools.clone()))),
+                    Some(Box::new(Variable(Rc::new(element.borrow().clone())))),
+                ),
+            ));
+            self.current_values.push((
+                element.id(),
+                Divide(
+                    Some(Box::new(Sum(tools).simplify().unwrap())),
+                    Some(Box::new(Variable(Rc::new(element.borrow().clone())))),
+                ),
+            ));
 
-        assert_ne!(self.node_combination_steps.len(), 0);
+            // The row sums the current leaving the nodes not held to ground by a source,
+            // resistors inside or outside of them drop out
+            let (from, to): (usize, usize) = match (group[*node1] != 0, group[*node2] != 0) {
+                (true, false) => (*node1, *node2),
+                (false, true) => (*node2, *node1),
+                _ => continue,
+            };
+            let leaving: Vec<Operation> = self.node_difference(from, to);
+            self.node_combination_steps.push(Divide(
+                Some(Box::new(Sum(leaving).simplify().unwrap())),
+                Some(Box::new(Variable(Rc::new(element.borrow().clone())))),
+            ));
+        }
+        // The current sources feeding the row's nodes balance the resistor currents
+        let injections: Vec<Operation> = self.current_source_terms();
+        self.node_combination_steps.extend(injections);
+
+        if self.node_combination_steps.is_empty() {
+            return Err(
+                "Every node is held by a voltage source, no KCL equation is left".to_string(),
+            );
+        }
 
         self.kcl_operations
             .push(Sum(self.node_combination_steps.clone()));
@@ -268,8 +393,8 @@ impl NodeStepSolver {
         self.node_combination_steps = self
             .node_combination_steps
             .iter()
-            .map(|x| x.simplify().unwrap_or_else(|| x.clone()))
-            .collect();
+            .map(simplified)
+            .collect::<Result<Vec<Operation>, String>>()?;
 
         Ok(())
     }
@@ -280,38 +405,45 @@ impl NodeStepSolver {
         let mut combination_steps = self
             .node_combination_steps
             .iter()
-            .map(|x| expand(x.clone()).unwrap_or_else(|_| x.clone()))
-            .collect::<Vec<Operation>>();
+            .map(expanded)
+            .collect::<Result<Vec<Operation>, String>>()?;
         self.kcl_operations.push(Sum(combination_steps.clone()));
         combination_steps = combination_steps
             .iter()
-            .map(|x| x.simplify().unwrap_or_else(|| x.clone()))
-            .collect::<Vec<Operation>>();
+            .map(simplified)
+            .collect::<Result<Vec<Operation>, String>>()?;
         let mut sum: Operation = Sum(combination_steps.clone());
         self.kcl_operations.push(sum.clone());
-        sum = sum.simplify().unwrap_or_else(|| sum.clone());
+        sum = simplified(&sum)?;
 
         // Include known values to extract coefficients
         sum.apply_variables();
 
-        // Group coefficients by variable (Tool)
-        let mut collected: Vec<(Operation, f64)> = sum
-            .get_variables()
+        // Group coefficients by node Tool, the columns follow the tool ids whatever the labels
+        let terms: Vec<Operation> = match sum {
+            Sum(list) => list,
+            other => vec![other],
+        };
+        let mut nodes: Vec<Tool> = self
+            .container
+            .borrow()
+            .nodes()
             .iter()
-            .map(|x| (x.clone(), 0.0))
+            .map(|x| x.upgrade().unwrap().borrow().clone())
+            .collect();
+        nodes.sort_by_key(|x| x.id);
+        self.node_coefficients = nodes
+            .into_iter()
+            .map(|node| {
+                let variable: Operation = Variable(Rc::new(node));
+                let coeff: f64 = terms
+                    .iter()
+                    .filter(|x| x.contains_variable(variable.clone()))
+                    .map(|x| x.get_coefficient().unwrap_or(0.0))
+                    .sum();
+                Value(coeff)
+            })
             .collect();
-        if let Sum(list) = sum.clone() {
-            for i in list {
-                for (var, coeff) in &mut collected {
-                    if i.contains_variable(var.deref().clone()) {
-                        *coeff += i.get_coefficient().unwrap_or(0.0);
-                    }
-                }
-            }
-        }
-        collected
-            .sort_by(|(a, _), (b, _)| a.latex_string().partial_cmp(&b.latex_string()).unwrap());
-        self.node_coefficients = collected.iter().map(|(_, coeff)| Value(*coeff)).collect();
 
         Ok(())
     }
@@ -384,7 +516,7 @@ impl NodeStepSolver {
         }
 
         steps.push(SubStep {
-            description: Some("Mark Nodes".to_string()),
+            description: Some(self.locale.text("mark_nodes")),
             result: None,
             operations: base_nodes
                 .iter()
@@ -393,7 +525,7 @@ impl NodeStepSolver {
         });
 
         steps.push(SubStep {
-            description: Some("Mark Supernodes".to_string()),
+            description: Some(self.locale.text("mark_supernodes")),
             result: None,
             operations: super_nodes
                 .iter()
@@ -402,15 +534,40 @@ impl NodeStepSolver {
         });
 
         steps.push(SubStep {
-            description: Some("Current entering and exiting each node.".to_string()),
+            description: Some(self.locale.text("node_currents")),
             result: None,
             operations: kcl_equations,
         });
 
+        if self.show_unsimplified {
+            steps.push(SubStep {
+                description: Some(self.locale.text("unsimplified_potentials")),
+                result: None,
+                operations: self
+                    .raw_current_values
+                    .iter()
+                    .map(|(id, equation)| {
+                        let mut i_element: Element = self
+                            .container
+                            .borrow()
+                            .get_element_by_id(*id)
+                            .unwrap()
+                            .borrow()
+                            .clone();
+                        i_element.set_name("i".to_string());
+                        Equal(
+                            Some(Box::new(Variable(Rc::new(i_element)))),
+                            Some(Box::new(equation.clone())),
+                        )
+                    })
+                    .collect(),
+            });
+        }
+
         let mut i_values: Vec<Operation> = Vec::new();
         self.current_values.iter().for_each(|(id, equation)| {
-            let i_element = (**self.container.borrow().get_element_by_id(*id)).clone();
-            let v_element = (**self.container.borrow().get_element_by_id(*id)).clone();
+            let i_element = (**self.container.borrow().get_element_by_id(*id).unwrap()).clone();
+            let v_element = (**self.container.borrow().get_element_by_id(*id).unwrap()).clone();
             i_element.borrow_mut().name = "i".to_string();
             v_element.borrow_mut().name = "V".to_string();
             let potential_expansion: Operation = match expand(equation.clone()) {
@@ -433,6 +590,7 @@ impl NodeStepSolver {
                             self.container
                                 .borrow()
                                 .get_element_by_id(*id)
+                                .unwrap()
                                 .borrow()
                                 .clone(),
                         )))),
@@ -442,20 +600,142 @@ impl NodeStepSolver {
             ));
         });
 
-        steps.push(SubStep{
-            description: Some("Use potential difference between nodes ($ N_{j, k} $) and Ohm's law to solve for current. Where $j, k$ are the two nodes that the element is connected to. We can treat GND as 0.".to_string()),
+        steps.push(SubStep {
+            description: Some(self.locale.text("element_currents")),
             result: None,
             operations: i_values,
         });
 
         Ok(Step {
-            title: Some("KCL Equations".to_string()),
-            description: Some("Outline the basis of the circuit using KCL equations".to_string()),
+            title: Some(self.locale.text("kcl_equations")),
+            description: Some(self.locale.text("kcl_equations_description")),
             result: None,
             sub_steps: steps,
         })
     }
 
+    /// The linear system written out as scalar equations in the node voltages.
+    ///
+    /// Each calculation node gives its KCL equation equal to the current the current sources
+    /// push into it, or the source equation when a voltage source ties it to ground. Sources
+    /// inside supernodes add their own constraints.
+    fn display_system_equations(&self) -> Step {
+        let container = self.container.borrow();
+        let into: Vec<f64> = self.current_source_injections(container.nodes().len());
+        let node_variable = |id: usize| {
+            Variable(Rc::new(
+                container
+                    .get_tool_by_id(self.node_index(id).unwrap())
+                    .borrow()
+                    .clone(),
+            ))
+        };
+        // (Positive node, negative node, source) for every voltage source
+        let sources: Vec<(usize, usize, &Rc<RefCell<Element>>)> = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == VoltageSrc)
+            .map(|(node1, node2, element)| {
+                let (positive, negative) = self.source_terminals(*node1, *node2, &element.borrow());
+                (positive, negative, element)
+            })
+            .collect();
+
+        let mut node_equations: Vec<Operation> = Vec::new();
+        for node in container.get_calculation_nodes().iter() {
+            let covered: Vec<usize> = container.subsumed_nodes(&node.borrow());
+            let grounded = sources.iter().find(|(positive, negative, _)| {
+                (*negative == 0 && covered.contains(positive))
+                    || (*positive == 0 && covered.contains(negative))
+            });
+            if let Some((positive, negative, source)) = grounded {
+                let value: f64 = source.borrow().value;
+                let (id, value) = match positive {
+                    0 => (*negative, -value),
+                    _ => (*positive, value),
+                };
+                node_equations.push(Equal(
+                    Some(Box::new(node_variable(id))),
+                    Some(Box::new(Value(value))),
+                ));
+                continue;
+            }
+
+            // Conductance times node voltage for the current leaving through each resistor
+            let mut coefficients: Vec<(usize, f64)> = Vec::new();
+            let mut add = |id: usize, value: f64| {
+                if id == 0 {
+                    return;
+                }
+                match coefficients.iter_mut().find(|x| x.0 == id) {
+                    Some(x) => x.1 += value,
+                    None => coefficients.push((id, value)),
+                }
+            };
+            for (node1, node2, element) in self.node_pairs.iter() {
+                let element = element.borrow();
+                if element.class != Resistor {
+                    continue;
+                }
+                let g: f64 = element.conductance();
+                match (covered.contains(node1), covered.contains(node2)) {
+                    (true, false) => {
+                        add(*node1, g);
+                        add(*node2, -g);
+                    }
+                    (false, true) => {
+                        add(*node2, g);
+                        add(*node1, -g);
+                    }
+                    _ => {}
+                }
+            }
+            coefficients.sort_by(|a, b| a.0.cmp(&b.0));
+            let terms: Vec<Operation> = coefficients
+                .iter()
+                .filter(|(_, value)| *value != 0.0)
+                .map(|(id, value)| Multiply(vec![Value(*value), node_variable(*id)]))
+                .collect();
+            let injected: f64 = covered.iter().fold(0.0, |total, x| total + into[*x]);
+            node_equations.push(Equal(
+                Some(Box::new(Sum(terms))),
+                Some(Box::new(Value(injected))),
+            ));
+        }
+
+        let source_equations: Vec<Operation> = sources
+            .iter()
+            .filter(|(positive, negative, _)| *positive != 0 && *negative != 0)
+            .map(|(positive, negative, source)| {
+                Equal(
+                    Some(Box::new(Sum(vec![
+                        node_variable(*positive),
+                        Negate(Some(Box::new(node_variable(*negative)))),
+                    ]))),
+                    Some(Box::new(Value(source.borrow().value))),
+                )
+            })
+            .collect();
+
+        Step {
+            title: Some(self.locale.text("system_of_equations")),
+            description: Some(self.locale.text("system_of_equations_description")),
+            result: None,
+            sub_steps: vec![
+                SubStep {
+                    description: Some(self.locale.text("node_equations")),
+                    result: None,
+                    operations: node_equations,
+                },
+                SubStep {
+                    description: Some(self.locale.text("supernode_sources")),
+                    result: None,
+                    operations: source_equations,
+                },
+            ],
+        }
+    }
+
     fn voltage_src_equations(&self) -> Result<Step, String> {
         let mut eq_steps: Vec<SubStep> = Vec::new();
         // Step 2.1.2 Find all voltage sources going between nodes including ground
@@ -466,24 +746,21 @@ impl NodeStepSolver {
             .for_each(|(node1, node2, element)| {
                 let mut tool2: Operation = Value(0.0);
                 let mut tool1: Operation = Value(0.0);
-                let mut id_1 = *node1;
-                let mut id_2 = *node2;
-                if *node1 != 0 {
-                    id_1 -= 1;
+                let (positive, negative) = self.source_terminals(*node1, *node2, &element.borrow());
+                if let Some(index) = self.node_index(positive) {
                     tool1 = Variable(Rc::new(
                         self.container
                             .borrow()
-                            .get_tool_by_id(id_1)
+                            .get_tool_by_id(index)
                             .borrow()
                             .clone(),
                     ));
                 }
-                if *node2 != 0 {
-                    id_2 -= 1;
+                if let Some(index) = self.node_index(negative) {
                     tool2 = Variable(Rc::new(
                         self.container
                             .borrow()
-                            .get_tool_by_id(id_2)
+                            .get_tool_by_id(index)
                             .borrow()
                             .clone(),
                     ));
@@ -513,32 +790,38 @@ impl NodeStepSolver {
         self.node_pairs
             .iter()
             .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .filter(|(node1, node2, _)| self.is_target(*node1, *node2))
             .for_each(|(node1, node2, element)| {
                 let mut i = element.borrow().clone();
                 i.set_name("i".to_string());
                 element_vector.push(Variable(Rc::new(i)));
                 let mut tools: Vec<Operation> = Vec::new();
-                if *node1 != 0 {
-                    tools.push(Value(self.node_voltages[*node1 - 1]));
+                let ground: f64 = -self.reference_offset;
+                if let Some(index) = self.node_index(*node1) {
+                    tools.push(Value(self.node_voltages[index]));
+                } else if ground != 0.0 {
+                    tools.push(Value(ground));
                 }
-                if *node2 != 0 {
-                    tools.push(Negate(Some(Box::new(Value(
-                        self.node_voltages[*node2 - 1],
-                    )))));
+                if let Some(index) = self.node_index(*node2) {
+                    tools.push(Negate(Some(Box::new(Value(self.node_voltages[index])))));
+                } else if ground != 0.0 {
+                    tools.push(Negate(Some(Box::new(Value(ground)))));
                 }
 
-                current_equations.push(
-                    Divide(
-                        Some(Box::new(Sum(tools).simplify().unwrap())),
-                        Some(Box::new(Value(element.borrow().value()))),
-                    )
-                    .simplify()
-                    .unwrap(),
-                );
+                let current: Operation = Divide(
+                    Some(Box::new(Sum(tools).simplify().unwrap())),
+                    Some(Box::new(Value(element.borrow().value()))),
+                )
+                .simplify()
+                .unwrap();
+                current_equations.push(match self.scale_currents {
+                    true => Text(scaled_current(current.value(), self.significant_figures)),
+                    false => current,
+                });
             });
 
         Ok(Step {
-            title: Some("Current Results".to_string()),
+            title: Some(self.locale.text("current_results")),
             description: None,
             result: Some(Equal(
                 Some(Box::new(Display(Rc::new(DVector::from_vec(
@@ -554,12 +837,12 @@ impl NodeStepSolver {
 
     fn display_connection_matrix(&self) -> Result<Step, String> {
         Ok(Step {
-            title: Some("Connection Matrix".to_string()),
+            title: Some(self.locale.text("connection_matrix")),
             description: None,
             result: Some(Display(Rc::new(self.connection_matrix.clone()))),
             sub_steps: vec![
                 SubStep {
-                    description: Some("Coefficients from the expanded KCL equations".to_string()),
+                    description: Some(self.locale.text("kcl_coefficients")),
                     result: Some(Equal(
                         Some(Box::new(Display(Rc::new(DVector::from(
                             self.node_coefficients.clone(),
@@ -578,7 +861,7 @@ impl NodeStepSolver {
                     operations: vec![],
                 },
                 SubStep {
-                    description: Some("Element connections between nodes.".to_string()),
+                    description: Some(self.locale.text("element_connections")),
                     result: Some(Display(Rc::new(
                         self.connection_matrix.clone().remove_rows(0, 1),
                     ))),
@@ -602,132 +885,1341 @@ impl NodeStepSolver {
                         .collect::<Vec<Operation>>(),
                 },
                 SubStep {
-                    description: Some("TODO explain this super step".to_string()),
+                    description: Some(self.locale.text("matrix_rows")),
                     result: None,
-                    operations: vec![],
+                    operations: self.row_labels.iter().map(|x| Text(x.clone())).collect(),
                 },
             ],
         })
     }
 
     fn display_solved_matrix(&self) -> Result<Step, String> {
-        let i_values: DVector<Operation> = DVector::from_vec(
-            self.container
-                .borrow()
-                .nodes()
-                .iter()
-                .map(|x| Variable(Rc::new(x.upgrade().unwrap().borrow().deref().clone())))
-                .collect::<Vec<Operation>>(),
-        );
+        let (i_values, voltages): (Vec<Operation>, Vec<f64>) = self
+            .container
+            .borrow()
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .zip(self.node_voltages.iter())
+            .filter(|(x, _)| self.is_target(x.borrow().id, x.borrow().id))
+            .map(|(x, v)| (Variable(Rc::new(x.borrow().deref().clone())), *v))
+            .unzip();
         let result: Operation = Equal(
-            Some(Box::new(Display(Rc::new(i_values.clone())))),
-            Some(Box::new(Display(Rc::new(self.node_voltages.clone())))),
+            Some(Box::new(Display(Rc::new(DVector::from_vec(i_values))))),
+            Some(Box::new(Display(Rc::new(DVector::from_vec(voltages))))),
         );
 
+        let mut sub_steps: Vec<SubStep> = vec![
+            SubStep {
+                description: Some(self.locale.text("invert_matrix")),
+                result: None,
+                operations: vec![
+                    Power(
+                        Some(Box::new(Display(Rc::new(self.connection_matrix.clone())))),
+                        Some(Box::new(Value(-1.0))),
+                    ),
+                    Display(Rc::new(self.inverse.clone())),
+                ],
+            },
+            SubStep {
+                description: Some(self.locale.text("multiply_inverse")),
+                result: Some(Display(Rc::new(self.node_voltages.clone()))),
+                operations: vec![Display(Rc::new(self.matrix_evaluation.clone()))],
+            },
+        ];
+        if let Some(reference) = self.container.borrow().reference_node() {
+            sub_steps.push(SubStep {
+                description: Some(format!(
+                    "Shift every node by {} so Node {} is the 0V reference",
+                    -self.reference_offset, reference
+                )),
+                result: Some(Display(Rc::new(self.node_voltages.clone()))),
+                operations: vec![],
+            });
+        }
+
         Ok(Step {
-            title: Some("Solve For Node Voltages".to_string()),
+            title: Some(self.locale.text("solve_node_voltages")),
             description: None,
             result: Some(result),
-            sub_steps: vec![
-                SubStep {
-                    description: Some("Invert the matrix".to_string()),
-                    result: None,
-                    operations: vec![
-                        Power(
-                            Some(Box::new(Display(Rc::new(self.connection_matrix.clone())))),
-                            Some(Box::new(Value(-1.0))),
-                        ),
-                        Display(Rc::new(self.inverse.clone())),
-                    ],
-                },
-                SubStep {
-                    description: Some(
-                        "Multiply the inverted matrix by the source voltages".to_string(),
-                    ),
-                    result: Some(Display(Rc::new(self.node_voltages.clone()))),
-                    operations: vec![Display(Rc::new(self.matrix_evaluation.clone()))],
-                },
-            ],
+            sub_steps,
         })
     }
 
     fn display_currents(&self) -> Result<Step, String> {
         let mut steps: Vec<SubStep> = Vec::new();
         let mut i_values: Vec<Operation> = Vec::new();
-        self.current_values.iter().for_each(|(id, equation)| {
-            let i_element = (**self.container.borrow().get_element_by_id(*id)).clone();
-            i_element.borrow_mut().name = "i".to_string();
-
-            i_values.push(Equal(
-                Some(Box::new(Variable(Rc::new(i_element.borrow().clone())))),
-                Some(Box::new(equation.clone())),
-            ));
-        });
+        self.current_values
+            .iter()
+            .filter(|(id, _)| self.is_target_element(*id))
+            .for_each(|(id, equation)| {
+                let i_element = (**self.container.borrow().get_element_by_id(*id).unwrap()).clone();
+                i_element.borrow_mut().name = "i".to_string();
+
+                i_values.push(Equal(
+                    Some(Box::new(Variable(Rc::new(i_element.borrow().clone())))),
+                    Some(Box::new(equation.clone())),
+                ));
+            });
 
-        steps.push(SubStep{
-            description: Some("Use potential difference between nodes ($ N_j $) and Ohm's law to solve for current.".to_string()),
+        steps.push(SubStep {
+            description: Some(self.locale.text("currents_ohms_law")),
             result: None,
             operations: i_values,
         });
 
+        if self.scale_currents {
+            let scaled: Vec<Operation> = self
+                .node_pairs
+                .iter()
+                .filter(|(_, _, element)| element.borrow().class == Resistor)
+                .zip(self.branch_currents().iter())
+                .filter(|((node1, node2, _), _)| self.is_target(*node1, *node2))
+                .map(|((_, _, element), current)| {
+                    let mut i_element: Element = element.borrow().clone();
+                    i_element.set_name("i".to_string());
+                    Equal(
+                        Some(Box::new(Variable(Rc::new(i_element)))),
+                        Some(Box::new(Text(scaled_current(
+                            *current,
+                            self.significant_figures,
+                        )))),
+                    )
+                })
+                .collect();
+            steps.push(SubStep {
+                description: Some(self.locale.text("scaled_currents")),
+                result: None,
+                operations: scaled,
+            });
+        }
+
         Ok(Step {
-            title: Some("Currents".to_string()),
-            description: Some(
-                "Evaluate the currents using the KCL equations and node voltages shown previously."
-                    .to_string(),
-            ),
+            title: Some(self.locale.text("currents")),
+            description: Some(self.locale.text("currents_description")),
             result: None,
             sub_steps: steps,
         })
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::container::Container;
-    use crate::solvers::node_step_solver::NodeStepSolver;
-    use crate::solvers::solver::Solver;
-    use crate::util::create_mna_container;
-    use nalgebra::DVector;
-    use operations::math::EquationMember;
-    use std::cell::RefCell;
-    use std::rc::Rc;
 
-    #[test]
-    fn test_node_pairs() {
-        let solver = setup_mna_solver();
-        println!("{:?}", solver.node_pairs);
-        assert_eq!(solver.node_pairs.len(), 5);
+    /// Store the solved currents and voltage drops on the container's elements.
+    fn write_back_currents(&self) {
+        let currents: DVector<f64> = self.branch_currents();
+        self.node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .zip(currents.iter())
+            .for_each(|((_, _, element), current)| {
+                let mut element = element.borrow_mut();
+                let resistance: f64 = element.value;
+                element.set_current_value(*current);
+                element.set_voltage_drop(current * resistance);
+            });
+        self.node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == CurrentSrc)
+            .for_each(|(_, _, element)| {
+                let value: f64 = element.borrow().value;
+                element.borrow_mut().set_current_value(value);
+            });
     }
 
-    #[test]
-    fn test_coefficients() {
-        let solver = setup_mna_solver();
-        assert_eq!(solver.node_coefficients.len(), 3);
-        assert_eq!(
-            solver
-                .node_coefficients
-                .into_iter()
-                .map(|x| x.value())
-                .collect::<Vec<f64>>(),
-            vec![-0.25, 0.375, 0.5]
+    /// Current through every resistor, in `node_pairs` order, from the solved node voltages.
+    ///
+    /// Multiplies the branch conductances, the branch to node incidence matrix and the
+    /// node potentials in one go. Ground is column 0 of the incidence matrix.
+    pub fn branch_currents(&self) -> DVector<f64> {
+        let resistors: Vec<&(usize, usize, Rc<RefCell<Element>>)> = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .collect();
+
+        let n: usize = self.node_voltages.len() + 1;
+        let mut incidence: DMatrix<f64> = DMatrix::zeros(resistors.len(), n);
+        let mut conductance: DVector<f64> = DVector::zeros(resistors.len());
+        for (k, (node1, node2, element)) in resistors.iter().enumerate() {
+            incidence[(k, *node1)] += 1.0;
+            incidence[(k, *node2)] -= 1.0;
+            conductance[k] = element.borrow().conductance();
+        }
+
+        let potentials: DVector<f64> = DVector::from_iterator(
+            n,
+            std::iter::once(-self.reference_offset).chain(self.node_voltages.iter().cloned()),
         );
+        DMatrix::from_diagonal(&conductance) * incidence * potentials
     }
 
-    #[test]
-    fn test_combination_steps() {
-        let solver = setup_mna_solver();
-        assert_eq!(solver.node_combination_steps.len(), 3);
+    /// (Node ID, net current into the node) from the solved voltages, ~0 when correct.
+    ///
+    /// Nodes joined by voltage sources share the residual of their supernode, as the source
+    /// currents are never solved for. Nodes tied to ground through a source are left out.
+    pub fn kcl_residuals(&self) -> Vec<(usize, f64)> {
+        let n: usize = self.node_voltages.len();
+        let mut into: Vec<f64> = self.current_source_injections(n);
+        self.node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .zip(self.branch_currents().iter())
+            .for_each(|((node1, node2, _), current)| {
+                into[*node1] -= current;
+                into[*node2] += current;
+            });
+
+        let group: Vec<usize> = self.source_groups(n);
+        (1..=n)
+            .filter(|k| group[*k] != 0)
+            .map(|k| {
+                let residual: f64 = (0..=n)
+                    .filter(|j| group[*j] == group[k])
+                    .map(|j| into[j])
+                    .sum();
+                (k, residual)
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_matrix() {
-        let solver = setup_mna_solver();
-        assert_eq!(solver.node_voltages.len(), 3);
-        assert_eq!(
-            solver.node_voltages,
-            DVector::from_vec(vec![20.0, 24.0, -8.0])
-        );
+    /// Group of every node, ground first, where nodes joined by voltage sources share the
+    /// lowest node id among them. Nodes held to ground are in group 0.
+    fn source_groups(&self, n: usize) -> Vec<usize> {
+        let mut group: Vec<usize> = (0..=n).collect();
+        self.node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == VoltageSrc)
+            .for_each(|(node1, node2, _)| {
+                let (keep, replace) = (
+                    group[*node1].min(group[*node2]),
+                    group[*node1].max(group[*node2]),
+                );
+                group
+                    .iter_mut()
+                    .filter(|x| **x == replace)
+                    .for_each(|x| *x = keep);
+            });
+        group
+    }
+
+    /// Current the current sources push across the boundary of the nodes the KCL row covers.
+    ///
+    /// Those are the nodes not held to ground by a voltage source, current leaves a current
+    /// source through its positive side as in the matrix solver.
+    fn supernode_injection(&self) -> f64 {
+        let n: usize = self.container.borrow().nodes().len();
+        let into: Vec<f64> = self.current_source_injections(n);
+        let group: Vec<usize> = self.source_groups(n);
+        (1..=n).filter(|k| group[*k] != 0).map(|k| into[k]).sum()
+    }
+
+    /// One term per current source crossing into the nodes the KCL row covers, signed so the
+    /// row's terms sum to zero. They total minus `supernode_injection`.
+    fn current_source_terms(&self) -> Vec<Operation> {
+        let container = self.container.borrow();
+        let group: Vec<usize> = self.source_groups(container.nodes().len());
+        let mut terms: Vec<Operation> = Vec::new();
+        for (node1, node2, element) in self.node_pairs.iter() {
+            let element = element.borrow();
+            if element.class != CurrentSrc {
+                continue;
+            }
+            let positive: usize = match container.terminal_node(&element, &element.positive) {
+                Some(node) => node,
+                None => continue,
+            };
+            let negative: usize = if positive == *node1 { *node2 } else { *node1 };
+            let source: Operation = Variable(Rc::new(element.clone()));
+            match (group[positive] != 0, group[negative] != 0) {
+                (true, false) => terms.push(source),
+                (false, true) => terms.push(Negate(Some(Box::new(source)))),
+                _ => {}
+            }
+        }
+        terms
+    }
+
+    /// Current pushed into every node by the current sources, ground first.
+    fn current_source_injections(&self, n: usize) -> Vec<f64> {
+        let container = self.container.borrow();
+        let mut into: Vec<f64> = vec![0.0; n + 1];
+        for (node1, node2, element) in self.node_pairs.iter() {
+            let element = element.borrow();
+            if element.class != CurrentSrc {
+                continue;
+            }
+            let positive: usize = match container.terminal_node(&element, &element.positive) {
+                Some(node) => node,
+                None => continue,
+            };
+            let negative: usize = if positive == *node1 { *node2 } else { *node1 };
+            into[positive] -= element.value();
+            into[negative] += element.value();
+        }
+        into
+    }
+
+    /// (Positive, negative) node of a voltage source given its node pair, ground being 0.
+    ///
+    /// Floating sources take the terminals their supernode recorded, the node pair order
+    /// follows tool creation and says nothing about polarity.
+    fn source_terminals(&self, node1: usize, node2: usize, source: &Element) -> (usize, usize) {
+        let container = self.container.borrow();
+        let recorded: Option<(usize, usize)> = container
+            .get_tools(SuperNode)
+            .iter()
+            .map(|x| x.upgrade().unwrap())
+            .find(|x| x.borrow().member_ids().last() == Some(&source.id))
+            .and_then(|x| x.borrow().terminals);
+        let positive: Option<usize> = match recorded {
+            Some((positive, _)) => Some(positive),
+            None => container.terminal_node(source, &source.positive),
+        };
+        match positive {
+            Some(positive) if positive == node2 => (node2, node1),
+            _ => (node1, node2),
+        }
+    }
+
+    /// `N_from - N_to` as the terms of a Sum, ground drops out.
+    fn node_difference(&self, from: usize, to: usize) -> Vec<Operation> {
+        let container = self.container.borrow();
+        let node = |x: usize| Variable(Rc::new(container.get_tool_by_id(x).borrow().clone()));
+        let mut tools: Vec<Operation> = Vec::new();
+        if let Some(index) = self.node_index(from) {
+            tools.push(node(index));
+        }
+        if let Some(index) = self.node_index(to) {
+            tools.push(Negate(Some(Box::new(node(index)))));
+        }
+        tools
+    }
+
+    /// Position of a node Tool in the node voltage vector, None for ground.
+    ///
+    /// Node tools take ids from 1 in the same order as the vector, ground is left out. See
+    /// `Container::node_index`, ids past the last node are None too.
+    pub fn node_index(&self, tool_id: usize) -> Option<usize> {
+        self.container.borrow().node_index(tool_id)
+    }
+
+    /// Node Tool ids in the order of the node voltage vector.
+    fn node_ids(&self) -> Vec<usize> {
+        self.container
+            .borrow()
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect()
+    }
+
+    /// Potential difference `V(a) - V(b)` between two solved nodes, node 0 being ground.
+    ///
+    /// None for a node the solve did not produce.
+    pub fn voltage_difference(&self, node_a: usize, node_b: usize) -> Option<f64> {
+        let voltage = |node: usize| match self.node_index(node) {
+            None => Some(-self.reference_offset),
+            Some(index) => self.node_voltages.get(index).copied(),
+        };
+        Some(voltage(node_a)? - voltage(node_b)?)
+    }
+
+    /// Solved voltage of every node keyed by node Tool id, ground is node 0.
+    ///
+    /// Ground reads 0V unless a reference node moved it, see `Container::set_reference_node`.
+    pub fn node_voltage_map(&self) -> HashMap<usize, f64> {
+        let mut voltages: HashMap<usize, f64> = HashMap::from([(0, -self.reference_offset)]);
+        voltages.extend(self.node_ids().into_iter().filter_map(|id| {
+            let index: usize = self.node_index(id)?;
+            Some((id, *self.node_voltages.get(index)?))
+        }));
+        voltages
+    }
+
+    /// `((a, b), V(a) - V(b))` for every ordered pair of distinct nodes, ground included.
+    ///
+    /// These are the open circuit voltages a Thevenin equivalent across a and b would use.
+    pub fn all_open_circuit_voltages(&self) -> Vec<((usize, usize), f64)> {
+        let mut nodes: Vec<usize> = vec![0];
+        nodes.extend(self.node_ids());
+        nodes
+            .iter()
+            .flat_map(|a| nodes.iter().filter(move |b| *b != a).map(move |b| (*a, *b)))
+            .filter_map(|(a, b)| Some(((a, b), self.voltage_difference(a, b)?)))
+            .collect()
+    }
+
+    /// (Node ID, ∂V/∂value) for every node with respect to the value of one element.
+    ///
+    /// Central differences, a copy of the circuit is solved with the value nudged either
+    /// way. Empty when the element does not exist or a perturbed circuit fails to solve.
+    pub fn sensitivity(&self, element_id: usize) -> Vec<(usize, f64)> {
+        let value: f64 = match self.container.borrow().get_element_by_id(element_id) {
+            Some(element) => element.borrow().value,
+            None => return vec![],
+        };
+
+        let solve = |value: f64| -> Option<DVector<f64>> {
+            let solved: Rc<RefCell<Container>> = self
+                .container
+                .borrow()
+                .solve_modified(|container| {
+                    container
+                        .get_element_by_id(element_id)
+                        .unwrap()
+                        .borrow_mut()
+                        .value = value;
+                })
+                .ok()?;
+            let voltages: Vec<f64> = solved
+                .borrow()
+                .nodes()
+                .iter()
+                .map(|x| x.upgrade().unwrap().borrow().value)
+                .collect();
+            Some(DVector::from_vec(voltages))
+        };
+
+        let step: f64 = value.abs().max(1.0) * 1e-6;
+        match (solve(value + step), solve(value - step)) {
+            (Some(up), Some(down)) if up.len() == down.len() => self
+                .node_ids()
+                .into_iter()
+                .filter_map(|id| {
+                    let index: usize = self.node_index(id)?;
+                    Some((id, (up.get(index)? - down.get(index)?) / (2.0 * step)))
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Significant figures shown by `summary_string`, 3 by default.
+    pub fn significant_figures(&mut self, figures: usize) -> &mut Self {
+        self.significant_figures = figures.max(1);
+        self
+    }
+
+    /// Show resistor currents with an A, mA or µA prefix in the current steps, off by default.
+    pub fn scale_currents(&mut self, scale: bool) -> &mut Self {
+        self.scale_currents = scale;
+        self
+    }
+
+    /// Solved node voltages and resistor currents, one per line with their units.
+    pub fn summary_string(&self) -> String {
+        let figures: usize = self.significant_figures;
+        let voltages = self.node_ids().into_iter().filter_map(|id| {
+            let voltage: f64 = *self.node_voltages.get(self.node_index(id)?)?;
+            Some(format!(
+                "V(node {}) = {} V",
+                id,
+                significant(voltage, figures)
+            ))
+        });
+        let currents = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .zip(self.branch_currents().iter())
+            .map(|((_, _, element), current)| {
+                format!(
+                    "I({}) = {} A",
+                    element.basic_string(),
+                    significant(*current, figures)
+                )
+            })
+            .collect::<Vec<String>>();
+        voltages.chain(currents).collect::<Vec<String>>().join("\n")
+    }
+
+    /// Every node voltage followed by every resistor current, as display ready rows.
+    pub fn solution_table(&self) -> Vec<SolutionRow> {
+        let branch_currents: DVector<f64> = self.branch_currents();
+        let voltages = self.node_ids().into_iter().map(|id| SolutionRow {
+            quantity_name: format!("N{}", id),
+            value: self.node_index(id).map_or(0.0, |i| self.node_voltages[i]),
+            unit: "V".to_string(),
+        });
+        let currents = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .zip(branch_currents.iter())
+            .map(|((_, _, element), current)| SolutionRow {
+                quantity_name: format!("i{}", element.borrow().id),
+                value: *current,
+                unit: "A".to_string(),
+            });
+        voltages.chain(currents).collect()
+    }
+
+    /// (Element ID, current into the positive terminal) for every voltage source.
+    ///
+    /// The node solve has no source current unknowns, they are recovered from KCL at every
+    /// node with the resistor and current source currents known. Sources joined in a loop
+    /// are not fixed by KCL and return an error.
+    pub fn source_currents(&self) -> Result<Vec<(usize, f64)>, StatusError> {
+        let n: usize = self.node_voltages.len();
+
+        // Current each node has to pass on through its voltage sources, the resistor
+        // currents leaving it less what the current sources push in.
+        let mut excess: Vec<f64> = self
+            .current_source_injections(n)
+            .iter()
+            .map(|x| -x)
+            .collect();
+        let resistors = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor);
+        for ((node1, node2, _), current) in resistors.zip(self.branch_currents().iter()) {
+            excess[*node1] += current;
+            excess[*node2] -= current;
+        }
+
+        // Source k takes current J_k out of its positive node, settle one source at a time
+        // from a node where it is the only source current left unknown.
+        let sources: Vec<(usize, usize, &Rc<RefCell<Element>>)> = self
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == VoltageSrc)
+            .map(|(node1, node2, element)| {
+                let (positive, negative) = self.source_terminals(*node1, *node2, &element.borrow());
+                (positive, negative, element)
+            })
+            .collect();
+        let mut currents: Vec<Option<f64>> = vec![None; sources.len()];
+        while let Some((node, k)) = (1..=n).find_map(|node| {
+            let unknown: Vec<usize> = (0..sources.len())
+                .filter(|k| currents[*k].is_none())
+                .filter(|k| sources[*k].0 == node || sources[*k].1 == node)
+                .collect();
+            match unknown.len() {
+                1 => Some((node, unknown[0])),
+                _ => None,
+            }
+        }) {
+            let (positive, negative, _) = sources[k];
+            let current: f64 = match positive == node {
+                true => -excess[node],
+                false => excess[node],
+            };
+            excess[positive] += current;
+            excess[negative] -= current;
+            currents[k] = Some(current);
+        }
+
+        sources
+            .iter()
+            .zip(currents.iter())
+            .map(|((_, _, source), current)| match current {
+                Some(current) => Ok((source.borrow().id, *current)),
+                None => Err(Known(format!(
+                    "Current through {} is not fixed by KCL, it is part of a loop of voltage sources",
+                    source.basic_string()
+                ))),
+            })
+            .collect()
+    }
+
+    /// (Element ID, resistance) each voltage source drives, its value over the current delivered.
+    ///
+    /// A negative resistance marks a source absorbing power.
+    pub fn source_loading(&self) -> Result<Vec<(usize, f64)>, StatusError> {
+        let container = self.container.borrow();
+        Ok(self
+            .source_currents()?
+            .iter()
+            .map(|(id, current)| {
+                let value: f64 = container.get_element_by_id(*id).unwrap().borrow().value;
+                (*id, value / -current)
+            })
+            .collect())
+    }
+
+    /// KCL equations as (node id, [(element id, coefficient)]) for each calculation node.
+    ///
+    /// Holds the same terms as `display_base_kcl_equations`. A coefficient of 1 marks
+    /// current leaving the node through the element, -1 marks current entering it.
+    pub fn kcl_equations_structured(&self) -> Vec<(usize, Vec<(usize, f64)>)> {
+        let container = self.container.borrow();
+        container
+            .get_calculation_nodes()
+            .iter()
+            .map(|node| {
+                let covered: Vec<usize> = container.subsumed_nodes(&node.borrow());
+                let terms: Vec<(usize, f64)> = node
+                    .borrow()
+                    .member_ids()
+                    .into_iter()
+                    .filter_map(|id| {
+                        let (node1, node2, element) =
+                            self.node_pairs.iter().find(|x| x.2.borrow().id == id)?;
+                        if element.borrow().class == VoltageSrc {
+                            return None;
+                        }
+                        match (covered.contains(node1), covered.contains(node2)) {
+                            (true, false) => Some((id, 1.0)),
+                            (false, true) => Some((id, -1.0)),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                (node.borrow().id, terms)
+            })
+            .collect()
+    }
+}
+
+impl Container {
+    /// Solve a copy changed by `modify` with the node step solver, this container is untouched.
+    pub(crate) fn solve_modified(
+        &self,
+        modify: impl FnOnce(&mut Container),
+    ) -> Result<Rc<RefCell<Container>>, StatusError> {
+        let mut container: Container = Container::new();
+        container.restore(self.snapshot());
+        modify(&mut container);
+        container.create_nodes()?;
+        container.create_super_nodes()?;
+        let container: Rc<RefCell<Container>> = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve()?;
+        Ok(container)
+    }
+}
+
+/// Surface a failed expansion, carrying on with the unexpanded form gives wrong coefficients.
+fn expanded(equation: &Operation) -> Result<Operation, String> {
+    expand(equation.clone()).map_err(|error| {
+        format!(
+            "Failed to expand KCL equation: {}: {:?}",
+            equation.equation_repr(),
+            error
+        )
+    })
+}
+
+/// Surface a failed simplification of a KCL equation.
+fn simplified(equation: &Operation) -> Result<Operation, String> {
+    equation.simplify().ok_or_else(|| {
+        format!(
+            "Failed to simplify KCL equation: {}",
+            equation.equation_repr()
+        )
+    })
+}
+
+/// Format a value rounded to a number of significant figures, keeping trailing zeros.
+fn significant(value: f64, figures: usize) -> String {
+    let magnitude: i32 = match value == 0.0 {
+        true => 0,
+        false => value.abs().log10().floor() as i32,
+    };
+    let decimals: usize = (figures as i32 - 1 - magnitude).max(0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+/// Format a current with the largest of A, mA or µA that keeps it at or above one.
+fn scaled_current(value: f64, figures: usize) -> String {
+    let (scaled, unit): (f64, &str) = match value.abs() {
+        x if x >= 1.0 || x == 0.0 => (value, "A"),
+        x if x >= 1e-3 => (value * 1e3, "mA"),
+        _ => (value * 1e6, "µA"),
+    };
+    let rounded: f64 = significant(scaled, figures).parse().unwrap_or(scaled);
+    format!("{} {}", rounded, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_known_error;
+    use crate::component::Component::{CurrentSrc, Ground, Resistor, VoltageSrc};
+    use crate::component::WaveformType::Sine;
+    use crate::container::Container;
+    use crate::elements::Element;
+    use crate::solvers::linear_system::LinearSystem;
+    use crate::solvers::node_matrix_solver::NodeMatrixSolver;
+    use crate::solvers::node_step_solver::{expanded, scaled_current, simplified, NodeStepSolver};
+    use crate::solvers::solver::{Locale, SolutionRow, Solver, Step};
+    use crate::util::{
+        create_current_source_container, create_divider_container, create_mna_container,
+    };
+    use crate::validation::StatusError::Known;
+    use nalgebra::DVector;
+    use operations::math::EquationMember;
+    use operations::operations::Operation;
+    use operations::prelude::{Sum, Text, Value};
+    use serde_json::json;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_node_pairs() {
+        let solver = setup_mna_solver();
+        println!("{:?}", solver.node_pairs);
+        assert_eq!(solver.node_pairs.len(), 5);
+    }
+
+    #[test]
+    fn test_waveform_ignored() {
+        let mut json: serde_json::Value =
+            serde_json::from_str(&create_mna_container().to_json()).unwrap();
+        json["elements"][5]["waveform"] = json!({"Sine": {"freq": 50.0, "phase": 0.5}});
+        let mut c = Container::from_json(&json.to_string()).unwrap();
+        assert_eq!(
+            c.get_element_by_id(5).unwrap().borrow().waveform,
+            Some(Sine {
+                freq: 50.0,
+                phase: 0.5
+            })
+        );
+        assert_eq!(c.get_element_by_id(4).unwrap().borrow().waveform, None);
+
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().unwrap();
+        let difference = solver.node_voltages.clone() - setup_mna_solver().node_voltages;
+        assert!(difference.iter().all(|x| x.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_solve_without_nodes() {
+        let c = create_mna_container();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        assert_known_error!(solver.solve(), "Call create_nodes before solving");
+    }
+
+    #[test]
+    fn test_coefficients() {
+        let solver = setup_mna_solver();
+        assert_eq!(solver.node_coefficients.len(), 3);
+        assert_eq!(
+            solver
+                .node_coefficients
+                .into_iter()
+                .map(|x| x.value())
+                .collect::<Vec<f64>>(),
+            vec![-0.25, 0.375, 0.5]
+        );
+    }
+
+    #[test]
+    fn test_combination_steps() {
+        let solver = setup_mna_solver();
+        assert_eq!(solver.node_combination_steps.len(), 3);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let solver = setup_mna_solver();
+        assert_eq!(solver.node_voltages.len(), 3);
+        // V4 holds N3 at 32 V over N2
+        let expected: DVector<f64> = DVector::from_vec(vec![20.0, -88.0 / 7.0, 136.0 / 7.0]);
+        assert!((&solver.node_voltages - expected).abs().max() < 1e-9);
+    }
+
+    #[test]
+    fn test_linear_system() {
+        let solver = setup_mna_solver();
+        let sources: DVector<f64> = DVector::from_vec(vec![0.0, 32.0, 20.0]);
+        let expected: DVector<f64> = DVector::from_vec(vec![20.0, -88.0 / 7.0, 136.0 / 7.0]);
+        let solved: DVector<f64> = solver.connection_matrix.solve_for(&sources).unwrap();
+        assert!((solved - expected).abs().max() < 1e-9);
+    }
+
+    #[test]
+    fn test_sensitivity() {
+        // 10V over two 10Ω resistors, the output is the node between them
+        let mut c: Container = create_divider_container(10.0, 10.0, 10.0);
+        c.create_nodes().unwrap();
+        let bottom: Element = c.get_element_by_id(3).unwrap().borrow().clone();
+        let output: usize = c.terminal_node(&bottom, &bottom.positive).unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().unwrap();
+        let v_out: f64 = solver.voltage_difference(output, 0).unwrap();
+        assert!((v_out - 5.0).abs() < 1e-9);
+
+        // Vout = 10·R3 / (R2 + R3)
+        let top = solver.sensitivity(2);
+        assert_eq!(top.len(), 2);
+        let (node, dv) = top[output - 1];
+        assert_eq!(node, output);
+        assert!(dv < 0.0);
+        assert!((dv + 0.05).abs() < 1e-6);
+
+        let (_, dv) = solver.sensitivity(3)[output - 1];
+        assert!((dv - 0.05).abs() < 1e-6);
+
+        assert!(solver.sensitivity(10).is_empty());
+        assert_eq!(solver.voltage_difference(output, 0), Some(v_out));
+    }
+
+    #[test]
+    fn test_solve_for_nodes() {
+        let solver = || -> NodeStepSolver {
+            let mut c: Container = create_mna_container();
+            c.create_nodes().unwrap();
+            c.create_super_nodes().unwrap();
+            Solver::new(Rc::new(RefCell::new(c)))
+        };
+        let currents = |steps: &Vec<Step>| -> Vec<String> {
+            steps[4].sub_steps[0]
+                .operations
+                .iter()
+                .map(|x| x.equation_repr())
+                .collect()
+        };
+
+        let full: Vec<String> = currents(&solver().solve().unwrap());
+        assert_eq!(full.len(), 3);
+
+        // Only R1 runs from N3 to ground
+        let mut trimmed = solver();
+        let steps = trimmed.solve_for_nodes(&[3]).unwrap();
+        assert_eq!(steps.len(), 6);
+        assert_eq!(currents(&steps), vec![full[0].clone()]);
+        assert_eq!(trimmed.node_voltages.len(), 3);
+
+        assert!(solver().solve_for_nodes(&[7]).is_err());
+    }
+
+    #[test]
+    fn test_locale() {
+        let steps = |locale: Locale| -> Vec<Step> {
+            let mut c: Container = create_mna_container();
+            c.create_nodes().unwrap();
+            c.create_super_nodes().unwrap();
+            let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.locale(locale).solve().unwrap()
+        };
+        let english_steps: Vec<Step> = steps(Locale::English);
+        let spanish_steps: Vec<Step> = steps(Locale::Spanish);
+        let english: Vec<Option<String>> = english_steps.iter().map(|x| x.title()).collect();
+        let spanish: Vec<Option<String>> = spanish_steps.iter().map(|x| x.title()).collect();
+
+        assert_eq!(english[0], Some("KCL Equations".to_string()));
+        assert_eq!(spanish[0], Some("Ecuaciones LCK".to_string()));
+        assert_eq!(spanish[1], Some("Sistema de Ecuaciones".to_string()));
+        assert_eq!(english.len(), spanish.len());
+        assert!(english.iter().zip(spanish.iter()).all(|(a, b)| a != b));
+        assert_eq!(Locale::Spanish.text("unknown"), "unknown");
+
+        assert_eq!(
+            spanish_steps[0].description,
+            Some("Plantear la base del circuito con ecuaciones LCK".to_string())
+        );
+        assert_eq!(
+            spanish_steps[0].sub_steps[0].description,
+            Some("Marcar Nodos".to_string())
+        );
+        assert_eq!(
+            english_steps[0].sub_steps[0].description,
+            Some("Mark Nodes".to_string())
+        );
+    }
+
+    #[test]
+    fn test_connection_matrix_rows() {
+        let solver = setup_mna_solver();
+        let step = solver.display_connection_matrix().unwrap();
+        assert!(step.sub_steps.iter().all(|x| !x
+            .description
+            .clone()
+            .unwrap_or_default()
+            .contains("TODO")));
+        assert_eq!(step.sub_steps.last().unwrap().operations.len(), 3);
+
+        assert_eq!(solver.row_labels.len(), solver.connection_matrix.nrows());
+        assert!(solver.row_labels[0].starts_with("Row 1: KCL"));
+        assert!(solver.row_labels[1..]
+            .iter()
+            .all(|x| x.contains("SRC(V)") && x.ends_with(" V")));
+    }
+
+    #[test]
+    fn test_current_source_beside_grounded_source() {
+        // V3 holds N2 at 10 V, KCL at N1 is N1/2 + (N1 - 10)/4 + 5 = 0
+        let fresh = || {
+            let mut c: Container = create_current_source_container();
+            c.create_nodes().unwrap();
+            c.create_super_nodes().unwrap();
+            Rc::new(RefCell::new(c))
+        };
+        let mut solver: NodeStepSolver = Solver::new(fresh());
+        solver.solve().unwrap();
+
+        // R2 only touches N2 and ground, it is not part of the row
+        assert_eq!(solver.node_combination_steps.len(), 3);
+        assert_eq!(
+            solver
+                .node_coefficients
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<f64>>(),
+            vec![0.75, -0.25]
+        );
+        assert!((solver.node_voltages[0] + 10.0 / 3.0).abs() < 1e-9);
+        assert!((solver.node_voltages[1] - 10.0).abs() < 1e-9);
+
+        let mut matrix: NodeMatrixSolver = Solver::new(fresh());
+        matrix.solve().unwrap();
+        assert!(
+            (matrix.node_voltages() - solver.node_voltages.clone())
+                .abs()
+                .max()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_current_source_into_supernode() {
+        // V3 floats between the two nodes, I4 pushes 3 A into the supernode they form
+        let mut c: Container = Container::from_adjacency(vec![
+            (1, 0, Resistor, 2.0),
+            (2, 0, Resistor, 2.0),
+            (1, 2, VoltageSrc, 4.0),
+            (0, 2, CurrentSrc, 3.0),
+        ])
+        .unwrap();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().unwrap();
+
+        assert_eq!(solver.supernode_injection(), 3.0);
+        assert_eq!(solver.node_combination_steps.len(), 3);
+        assert!((solver.node_voltages[0] - 5.0).abs() < 1e-9);
+        assert!((solver.node_voltages[1] - 1.0).abs() < 1e-9);
+        assert!(solver.kcl_residuals().iter().all(|(_, x)| x.abs() < 1e-9));
+
+        // Everything R1 draws from N1 comes in through the source
+        let currents = solver.source_currents().unwrap();
+        assert_eq!(currents.len(), 1);
+        assert!((currents[0].1 + 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coefficient_order() {
+        // Rk ties node k to ground, a chain of floating sources leaves a single KCL row
+        let mut edges = vec![];
+        edges.extend((1..=10usize).map(|k| (k, 0, Resistor, k as f64)));
+        edges.extend((1..10).map(|k| (k, k + 1, VoltageSrc, 1.0)));
+        let mut c: Container = Container::from_adjacency(edges).unwrap();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        assert_eq!(c.nodes().len(), 10);
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().unwrap();
+
+        let coefficients: Vec<f64> = solver.node_coefficients.iter().map(|x| x.value()).collect();
+        assert_eq!(coefficients.len(), 10);
+        for (k, coefficient) in coefficients.iter().enumerate() {
+            assert!((coefficient - 1.0 / (k + 1) as f64).abs() < 1e-12);
+        }
+        assert!(solver.kcl_residuals().iter().all(|(_, x)| x.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_node_index() {
+        let solver = setup_mna_solver();
+        assert_eq!(solver.node_index(0), None);
+        assert_eq!(solver.node_index(1), Some(0));
+        assert_eq!(solver.node_index(3), Some(2));
+        assert_eq!(solver.node_index(4), None);
+
+        // The node tools, the KCL variables and the solved vector agree on every node
+        let container = solver.container.borrow();
+        for node in container.nodes().iter().map(|x| x.upgrade().unwrap()) {
+            let index: usize = solver.node_index(node.borrow().id).unwrap();
+            assert_eq!(solver.node_voltages[index], node.borrow().value);
+        }
+        let resistors = solver
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor);
+        for ((node1, node2, element), current) in resistors.zip(solver.branch_currents().iter()) {
+            let drop: f64 = solver.voltage_difference(*node1, *node2).unwrap();
+            assert!((drop / element.borrow().value - current).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_kcl_residuals() {
+        // Node 1 sits on the grounded 20V source, nodes 2 and 3 form the 32V supernode
+        let solver = setup_mna_solver();
+        let residuals: Vec<(usize, f64)> = solver.kcl_residuals();
+        assert_eq!(
+            residuals.iter().map(|x| x.0).collect::<Vec<usize>>(),
+            vec![2, 3]
+        );
+        assert!(residuals.iter().all(|(_, x)| x.abs() < 1e-9));
+
+        let mut c: Container = Container::resistor_ladder(3, 2.0, 5.0);
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut ladder: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        ladder.solve().unwrap();
+        let residuals: Vec<(usize, f64)> = ladder.kcl_residuals();
+        assert_eq!(residuals.len(), 3);
+        assert!(residuals.iter().all(|(_, x)| x.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_solve_fast() {
+        let solver = setup_mna_solver();
+
+        let mut c: Container = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut fast: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let voltages: DVector<f64> = fast.solve_fast().unwrap();
+        assert_eq!(voltages, solver.node_voltages);
+        assert_eq!(fast.node_voltages, solver.node_voltages);
+    }
+
+    #[test]
+    fn test_expand_failure() {
+        let equation: Operation = Sum(vec![Value(1.0), Value(2.0)]);
+        assert!(expanded(&equation).is_ok());
+        assert!(simplified(&equation).is_ok());
+
+        // Every KCL equation of a normal circuit expands
+        let solver = setup_mna_solver();
+        assert!(!solver.node_coefficients.is_empty());
+
+        // A KCL equation holding a term with no expansion stops the coefficients
+        let mut c: Container = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.setup_node_equations().unwrap();
+        solver
+            .node_combination_steps
+            .push(Text("I_{x}".to_string()));
+        let error: Option<String> = solver.setup_node_coefficients().err();
+        assert!(matches!(
+            error,
+            Some(x) if x.starts_with("Failed to expand KCL equation: ")
+        ));
+        assert!(solver.node_coefficients.is_empty());
+    }
+
+    #[test]
+    fn test_scale_currents() {
+        assert_eq!(scaled_current(0.0025, 3), "2.5 mA");
+        assert_eq!(scaled_current(-0.0000125, 3), "-12.5 µA");
+        assert_eq!(scaled_current(3.0, 3), "3 A");
+
+        let mut container: Container = Container::new();
+        container.add_element_no_id(Element::new(Ground, 0., vec![1, 2], vec![]));
+        container.add_element_no_id(Element::new(VoltageSrc, 2.5, vec![2], vec![0]));
+        container.add_element_no_id(Element::new(Resistor, 1000., vec![1], vec![0]));
+        container.create_nodes().unwrap();
+        container.create_super_nodes().unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(container)));
+        solver.scale_currents(true).solve().unwrap();
+
+        let step = solver.current_steps().unwrap();
+        assert!(step.result.unwrap().equation_repr().contains("2.5 mA"));
+        assert_eq!(solver.display_currents().unwrap().sub_steps.len(), 2);
+    }
+
+    #[test]
+    fn test_summary_string() {
+        let mut solver = setup_mna_solver();
+        let summary: String = solver.summary_string();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "V(node 1) = 20.0 V");
+        assert_eq!(lines[1], "V(node 2) = -12.6 V");
+        assert_eq!(lines[2], "V(node 3) = 19.4 V");
+        assert!(lines[3].starts_with("I(R1) = "));
+        assert!(lines[4].starts_with("I(R2) = "));
+        assert_eq!(lines[5], "I(R3) = -1.57 A");
+
+        let summary: String = solver.significant_figures(1).summary_string();
+        assert!(summary.starts_with("V(node 1) = 20 V\n"));
+        assert!(summary.ends_with("I(R3) = -2 A"));
+    }
+
+    #[test]
+    fn test_all_open_circuit_voltages() {
+        // Node voltages are [20, -88/7, 136/7]
+        let solver = setup_mna_solver();
+        let voltages: Vec<((usize, usize), f64)> = solver.all_open_circuit_voltages();
+        assert_eq!(voltages.len(), 12);
+        for ((a, b), voltage) in voltages.iter() {
+            let (_, reverse) = voltages.iter().find(|x| x.0 == (*b, *a)).unwrap();
+            assert_eq!(*voltage, -reverse);
+        }
+        let find = |pair: (usize, usize)| voltages.iter().find(|x| x.0 == pair).unwrap().1;
+        assert!((find((3, 2)) - 32.0).abs() < 1e-9);
+        assert_eq!(find((1, 0)), 20.0);
+        assert!((find((0, 3)) + 136.0 / 7.0).abs() < 1e-9);
+        assert!(voltages.iter().all(|((a, b), _)| a != b));
+    }
+
+    #[test]
+    fn test_node_voltage_map() {
+        let solver = setup_mna_solver();
+        let voltages = solver.node_voltage_map();
+        assert_eq!(voltages.len(), 4);
+        assert_eq!(voltages[&0], 0.0);
+        assert_eq!(voltages[&1], 20.0);
+        assert!((voltages[&2] + 88.0 / 7.0).abs() < 1e-9);
+        assert!((voltages[&3] - 136.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_voltage_difference() {
+        // Node voltages are [20, -88/7, 136/7]
+        let solver = setup_mna_solver();
+        let difference = |a: usize, b: usize| solver.voltage_difference(a, b).unwrap();
+        assert!((difference(3, 2) - 32.0).abs() < 1e-9);
+        assert!((difference(3, 1) + 4.0 / 7.0).abs() < 1e-9);
+        assert_eq!(difference(1, 0), 20.0);
+        assert!((difference(0, 2) - 88.0 / 7.0).abs() < 1e-9);
+        assert_eq!(solver.voltage_difference(2, 2), Some(0.0));
+        assert_eq!(solver.voltage_difference(4, 0), None);
+
+        let unsolved: NodeStepSolver = Solver::new(Rc::new(RefCell::new(create_mna_container())));
+        assert_eq!(unsolved.voltage_difference(1, 0), None);
+    }
+
+    #[test]
+    fn test_branch_currents() {
+        let solver = setup_mna_solver();
+        let voltage = |node: usize| match solver.node_index(node) {
+            Some(index) => solver.node_voltages[index],
+            None => 0.0,
+        };
+        let looped: Vec<f64> = solver
+            .node_pairs
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == Resistor)
+            .map(|(node1, node2, element)| {
+                (voltage(*node1) - voltage(*node2)) / element.borrow().value
+            })
+            .collect();
+
+        assert_eq!(solver.branch_currents(), DVector::from_vec(looped));
+        let expected: DVector<f64> = DVector::from_vec(vec![68.0 / 7.0, 57.0 / 7.0, -11.0 / 7.0]);
+        assert!((solver.branch_currents() - expected).abs().max() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_temperature() {
+        let mut c: Container = create_mna_container();
+        let resistor = c.get_elements()[3].clone();
+        let nominal = resistor.borrow().clone().with_temperature_coefficient(0.01);
+        *resistor.borrow_mut() = nominal;
+
+        c.apply_temperature(25.0);
+        assert_eq!(resistor.borrow().value, 8.0);
+        c.apply_temperature(125.0);
+        c.apply_temperature(125.0);
+        assert_eq!(resistor.borrow().value, 16.0);
+
+        c.create_nodes().unwrap();
+        c.create_super_nodes();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().expect("Unable to solve");
+
+        // Supernode KCL: (V2 - 20)/4 + V2/16 + (V2 + 32)/2 = 0
+        let current = solver.branch_currents()[2];
+        assert!(current.abs() < 11.0 / 7.0);
+        assert!((current + 11.0 / 13.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_solution_table() {
+        let solver = setup_mna_solver();
+        let table = solver.solution_table();
+        assert_eq!(table.len(), 6);
+        assert_eq!(
+            table.iter().filter(|x| x.unit == "V").count(),
+            solver.container.borrow().nodes().len()
+        );
+        assert_eq!(table.iter().filter(|x| x.unit == "A").count(), 3);
+        assert_eq!(
+            table[0],
+            SolutionRow {
+                quantity_name: "N1".to_string(),
+                value: 20.0,
+                unit: "V".to_string(),
+            }
+        );
+        assert_eq!(table[5].quantity_name, "i3");
+        assert!((table[5].value + 11.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_volt_source() {
+        let mut c: Container = Container::new();
+        c.add_element_core(Element::new_full(Ground, 0.0, vec![1, 4], vec![], 0));
+        c.add_element_core(Element::new_full(VoltageSrc, 10.0, vec![2], vec![0], 1));
+        c.add_element_core(Element::new_full(Resistor, 1.0, vec![1], vec![3], 2));
+        c.add_element_core(Element::new_full(VoltageSrc, 0.0, vec![2], vec![4], 3));
+        c.add_element_core(Element::new_full(Resistor, 1.0, vec![3], vec![0], 4));
+        assert!(c.validate().is_ok());
+        c.create_nodes().unwrap();
+        c.create_super_nodes();
+        assert!(c.validate().is_ok());
+
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        solver.solve().expect("Unable to solve");
+        assert_eq!(
+            solver.node_voltages,
+            DVector::from_vec(vec![10.0, 5.0, 5.0])
+        );
+        assert_eq!(solver.branch_currents(), DVector::from_vec(vec![5.0, 5.0]));
+    }
+
+    #[test]
+    fn test_current_through() {
+        let mut c: Container = create_mna_container();
+        assert_eq!(c.current_through(3), None);
+        c.create_nodes().unwrap();
+        assert_eq!(c.current_through(3), None);
+
+        let solver = setup_mna_solver();
+        let container = solver.container.borrow();
+        assert!((container.current_through(3).unwrap() + 11.0 / 7.0).abs() < 1e-9);
+        assert!((container.current_through(1).unwrap() - 68.0 / 7.0).abs() < 1e-9);
+        assert_eq!(container.current_through(4), None);
+        assert_eq!(container.current_through(10), None);
+    }
+
+    #[test]
+    fn test_voltage_across() {
+        assert_eq!(create_mna_container().voltage_across(1), None);
+
+        let solver = setup_mna_solver();
+        let container = solver.container.borrow();
+        for id in 1..=3 {
+            let resistance: f64 = container.get_element_by_id(id).unwrap().borrow().value;
+            assert_eq!(
+                container.voltage_across(id),
+                container.current_through(id).map(|x| x * resistance)
+            );
+        }
+        // Across the node pair, V4 is walked from its negative node
+        assert!((container.voltage_across(4).unwrap() + 32.0).abs() < 1e-9);
+        assert_eq!(container.voltage_across(5), Some(20.0));
+    }
+
+    #[test]
+    fn test_kcl_equations_structured() {
+        let solver = setup_mna_solver();
+        let equations = solver.kcl_equations_structured();
+        assert_eq!(equations.len(), 2);
+        assert_eq!(equations[0], (1, vec![(2, 1.0)]));
+        assert_eq!(equations[1], (4, vec![(1, 1.0), (2, -1.0), (3, 1.0)]));
+    }
+
+    #[test]
+    fn test_reference_node() {
+        let solve_with_reference = |reference: usize| -> DVector<f64> {
+            let mut c: Container = create_mna_container();
+            c.create_nodes().unwrap();
+            c.create_super_nodes().unwrap();
+            c.set_reference_node(reference).unwrap();
+            let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+            solver.solve().expect("Unable to solve");
+            solver.node_voltages
+        };
+
+        let first = solve_with_reference(1);
+        let second = solve_with_reference(2);
+        assert_eq!(first[0], 0.0);
+        assert_eq!(second[1], 0.0);
+        for a in 0..3 {
+            for b in 0..3 {
+                assert!(((first[a] - first[b]) - (second[a] - second[b])).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_source_loading() {
+        let solver = setup_mna_solver();
+        let currents = solver.source_currents().unwrap();
+        assert_eq!(currents.len(), 2);
+        assert!((currents[0].1 + 68.0 / 7.0).abs() < 1e-9);
+        assert!((currents[1].1 + 57.0 / 7.0).abs() < 1e-9);
+
+        let loading = solver.source_loading().unwrap();
+        assert_eq!(loading.len(), 2);
+        for ((id, resistance), (_, current)) in loading.iter().zip(currents.iter()) {
+            let value: f64 = solver
+                .container
+                .borrow()
+                .get_element_by_id(*id)
+                .unwrap()
+                .borrow()
+                .value;
+            assert!((resistance - value / -current).abs() < 1e-9);
+        }
+        assert_eq!(loading[0].0, 4);
+        assert!((loading[0].1 - 56.0 / 17.0).abs() < 1e-9);
+        assert!((loading[1].1 - 140.0 / 57.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_system_equations() {
+        let mut c: Container = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes();
+        let calculation_nodes: usize = c.get_calculation_nodes().len();
+        let node_count: usize = c.nodes().len();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let steps = solver.solve().unwrap();
+
+        let system = &steps[1];
+        assert_eq!(system.title(), Some("System of Equations".to_string()));
+        assert_eq!(system.sub_steps[0].operations.len(), calculation_nodes);
+        let equations: usize = system.sub_steps.iter().map(|x| x.operations.len()).sum();
+        assert_eq!(equations, node_count);
+
+        // Opting out goes straight from the KCL equations to the matrix
+        let mut c: Container = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let hidden = solver.show_system(false).solve().unwrap();
+        assert_eq!(hidden.len(), steps.len() - 1);
+        assert_ne!(hidden[1].title(), Some("System of Equations".to_string()));
+    }
+
+    #[test]
+    fn test_show_unsimplified() {
+        let descriptions = |show: bool| -> Vec<String> {
+            let mut c: Container = create_mna_container();
+            c.create_nodes().unwrap();
+            c.create_super_nodes();
+            let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+            let steps = solver.show_unsimplified(show).solve().unwrap();
+            steps[0]
+                .sub_steps
+                .iter()
+                .map(|x| x.description.clone().unwrap_or_default())
+                .collect()
+        };
+
+        let plain: Vec<String> = descriptions(false);
+        let shown: Vec<String> = descriptions(true);
+        assert_eq!(shown.len(), plain.len() + 1);
+        let raw = shown
+            .iter()
+            .position(|x| x.contains("before simplification"))
+            .unwrap();
+        let simplified = shown
+            .iter()
+            .position(|x| x.starts_with("Use potential difference"))
+            .unwrap();
+        assert!(raw < simplified);
+        assert!(!plain.iter().any(|x| x.contains("before simplification")));
     }
 
     fn setup_mna_solver() -> NodeStepSolver {
@@ -0,0 +1,393 @@
+use crate::component::Component::{CurrentSrc, Diode, Ground, Resistor, VoltageSrc};
+use crate::container::Container;
+use crate::elements::Element;
+use crate::solvers::linear_system::LinearSystem;
+use crate::solvers::solver::{Solver, Step, SubStep};
+use crate::util::PrettyPrint;
+use crate::validation::StatusError;
+use crate::validation::StatusError::Known;
+use nalgebra::{DMatrix, DVector};
+use operations::prelude::{Display, Equal, Operation, Value, Variable};
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// Largest change in volts between iterations that still counts as converged
+const TOLERANCE: f64 = 1e-9;
+const MAX_ITERATIONS: usize = 100;
+/// Conductance kept across every diode so a reverse biased one does not leave a node floating
+const GMIN: f64 = 1e-12;
+
+/// Node solver for circuits with nonlinear elements, using Newton-Raphson.
+///
+/// Every iteration replaces each diode with the conductance and current source tangent to
+/// its curve at the previous guess, then solves the resulting linear (MNA) system.
+pub struct NonlinearNodeSolver {
+    container: Rc<RefCell<Container>>,
+    nodes: Vec<usize>, // Node IDs in matrix row order
+    branches: Vec<(usize, usize, Rc<RefCell<Element>>)>, // (Positive node, Negative node, Element)
+    node_voltages: DVector<f64>,
+    iterations: Vec<DVector<f64>>, // Node voltages after each iteration
+}
+
+impl Solver for NonlinearNodeSolver {
+    fn new(container: Rc<RefCell<Container>>) -> Self {
+        NonlinearNodeSolver {
+            container,
+            nodes: vec![],
+            branches: vec![],
+            node_voltages: DVector::zeros(0),
+            iterations: vec![],
+        }
+    }
+
+    /// Returns the steps to find the operating point of the circuit.
+    fn solve(&mut self) -> Result<Vec<Step>, StatusError> {
+        // SETUP and CALCULATIONS
+        self.setup_branches()?;
+        self.iterate()?;
+        self.write_back();
+
+        // FORMATTING and OUTPUT
+        let mut steps: Vec<Step> = Vec::new();
+        steps.push(self.display_iterations());
+        steps.push(self.display_operating_point());
+        Ok(steps)
+    }
+}
+
+impl NonlinearNodeSolver {
+    /// Find the node on both sides of every element, 0 being ground.
+    fn setup_branches(&mut self) -> Result<(), StatusError> {
+        let container = self.container.borrow();
+        let nodes: Vec<usize> = container
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().id)
+            .collect();
+        if nodes.is_empty() {
+            return Err(Known("No nodes to solve".to_string()));
+        }
+
+        let terminal = |element: &Element, side: &[usize]| -> Result<usize, StatusError> {
+            container.terminal_node(element, side).ok_or_else(|| {
+                Known(format!(
+                    "Element {} is not connected on both sides",
+                    element.basic_string()
+                ))
+            })
+        };
+
+        self.branches.clear();
+        for element in container.get_elements().iter() {
+            let e = element.borrow();
+            if !e.enabled {
+                continue;
+            }
+            match e.class {
+                Ground => continue,
+                Resistor | VoltageSrc | CurrentSrc | Diode => {}
+                _ => {
+                    return Err(Known(format!(
+                        "Unsupported element in nonlinear solve: {}",
+                        e.basic_string()
+                    )))
+                }
+            }
+            let positive: usize = terminal(&e, &e.positive)?;
+            let negative: usize = terminal(&e, &e.negative)?;
+            self.branches.push((positive, negative, element.clone()));
+        }
+        self.nodes = nodes;
+        Ok(())
+    }
+
+    /// Matrix row of a node, None for ground.
+    fn row(&self, node: usize) -> Option<usize> {
+        self.nodes.iter().position(|x| *x == node)
+    }
+
+    fn iterate(&mut self) -> Result<(), StatusError> {
+        let n: usize = self.nodes.len();
+        let sources: usize = self
+            .branches
+            .iter()
+            .filter(|(_, _, element)| element.borrow().class == VoltageSrc)
+            .count();
+
+        let mut voltages: DVector<f64> = DVector::zeros(n);
+        let mut diode_voltages: Vec<f64> = vec![0.0; self.branches.len()];
+        self.iterations.clear();
+        for _ in 0..MAX_ITERATIONS {
+            let mut matrix: DMatrix<f64> = DMatrix::zeros(n + sources, n + sources);
+            let mut rhs: DVector<f64> = DVector::zeros(n + sources);
+            let mut k: usize = n;
+            for (b, (positive, negative, element)) in self.branches.iter().enumerate() {
+                let element = element.borrow();
+                let (p, q) = (self.row(*positive), self.row(*negative));
+                match element.class {
+                    Resistor => stamp_conductance(&mut matrix, p, q, element.conductance()),
+                    CurrentSrc => stamp_current(&mut rhs, p, q, element.value),
+                    VoltageSrc => {
+                        if let Some(p) = p {
+                            matrix[(p, k)] += 1.0;
+                            matrix[(k, p)] += 1.0;
+                        }
+                        if let Some(q) = q {
+                            matrix[(q, k)] -= 1.0;
+                            matrix[(k, q)] -= 1.0;
+                        }
+                        rhs[k] = element.value;
+                        k += 1;
+                    }
+                    Diode => {
+                        // Linearized around the previous junction voltage
+                        let vd: f64 = diode_voltages[b];
+                        let (current, conductance) = diode_model(&element, vd);
+                        stamp_conductance(&mut matrix, p, q, conductance + GMIN);
+                        stamp_current(&mut rhs, p, q, current - conductance * vd);
+                    }
+                    _ => {}
+                }
+            }
+
+            let solution: DVector<f64> = matrix.solve_for(&rhs)?;
+            let next: DVector<f64> = solution.rows(0, n).into_owned();
+            let mut converged: bool = (&next - &voltages).amax() < TOLERANCE;
+
+            let potential = |node: usize| self.row(node).map_or(0.0, |x| next[x]);
+            for (b, (positive, negative, element)) in self.branches.iter().enumerate() {
+                let element = element.borrow();
+                if element.class != Diode {
+                    continue;
+                }
+                let target: f64 = potential(*positive) - potential(*negative);
+                let limited: f64 = limit_junction(&element, target, diode_voltages[b]);
+                if (limited - target).abs() > TOLERANCE
+                    || (limited - diode_voltages[b]).abs() > TOLERANCE
+                {
+                    converged = false;
+                }
+                diode_voltages[b] = limited;
+            }
+
+            voltages = next;
+            self.iterations.push(voltages.clone());
+            if converged {
+                self.node_voltages = voltages;
+                return Ok(());
+            }
+        }
+
+        Err(Known(format!(
+            "Nonlinear solve did not converge after {} iterations",
+            MAX_ITERATIONS
+        )))
+    }
+
+    /// Store the node voltages and the resistor and diode currents on the container.
+    fn write_back(&self) {
+        let container = self.container.borrow();
+        for (i, node) in container.nodes().iter().enumerate() {
+            node.upgrade()
+                .unwrap()
+                .borrow_mut()
+                .set_value(self.node_voltages[i]);
+        }
+        for (positive, negative, current) in self.currents() {
+            let element = container.get_element_by_id(current.0).unwrap();
+            let voltage: f64 = self.potential(positive) - self.potential(negative);
+            let mut element = element.borrow_mut();
+            element.set_current_value(current.1);
+            element.set_voltage_drop(voltage);
+        }
+    }
+
+    fn potential(&self, node: usize) -> f64 {
+        self.row(node).map_or(0.0, |x| self.node_voltages[x])
+    }
+
+    /// (Positive node, Negative node, (Element ID, current)) for every resistor and diode.
+    fn currents(&self) -> Vec<(usize, usize, (usize, f64))> {
+        self.branches
+            .iter()
+            .filter_map(|(positive, negative, element)| {
+                let element = element.borrow();
+                let voltage: f64 = self.potential(*positive) - self.potential(*negative);
+                let current: f64 = match element.class {
+                    Resistor => voltage * element.conductance(),
+                    Diode => diode_model(&element, voltage).0,
+                    _ => return None,
+                };
+                Some((*positive, *negative, (element.id, current)))
+            })
+            .collect()
+    }
+
+    /// Current through an element at the operating point, positive to negative side.
+    pub fn current_through(&self, element_id: usize) -> Option<f64> {
+        self.currents()
+            .iter()
+            .find(|(_, _, (id, _))| *id == element_id)
+            .map(|(_, _, (_, current))| *current)
+    }
+
+    fn node_variables(&self) -> DVector<Operation> {
+        DVector::from_vec(
+            self.container
+                .borrow()
+                .nodes()
+                .iter()
+                .map(|x| Variable(Rc::new(x.upgrade().unwrap().borrow().deref().clone())))
+                .collect::<Vec<Operation>>(),
+        )
+    }
+
+    fn display_iterations(&self) -> Step {
+        let variables: DVector<Operation> = self.node_variables();
+        let sub_steps: Vec<SubStep> = self
+            .iterations
+            .iter()
+            .enumerate()
+            .map(|(k, voltages)| SubStep {
+                description: Some(format!("Iteration {}", k + 1)),
+                result: Some(Equal(
+                    Some(Box::new(Display(Rc::new(variables.clone())))),
+                    Some(Box::new(Display(Rc::new(voltages.clone())))),
+                )),
+                operations: vec![],
+            })
+            .collect();
+
+        Step {
+            title: Some("Newton-Raphson Iterations".to_string()),
+            description: Some(
+                "Replace each diode with its tangent at the last guess and solve the linear circuit until the node voltages settle."
+                    .to_string(),
+            ),
+            result: None,
+            sub_steps,
+        }
+    }
+
+    fn display_operating_point(&self) -> Step {
+        let container = self.container.borrow();
+        let i_values: Vec<Operation> = self
+            .currents()
+            .iter()
+            .map(|(_, _, (id, current))| {
+                let mut i_element: Element =
+                    container.get_element_by_id(*id).unwrap().borrow().clone();
+                i_element.set_name("i".to_string());
+                Equal(
+                    Some(Box::new(Variable(Rc::new(i_element)))),
+                    Some(Box::new(Value(*current))),
+                )
+            })
+            .collect();
+
+        Step {
+            title: Some("Operating Point".to_string()),
+            description: None,
+            result: Some(Equal(
+                Some(Box::new(Display(Rc::new(self.node_variables())))),
+                Some(Box::new(Display(Rc::new(self.node_voltages.clone())))),
+            )),
+            sub_steps: vec![SubStep {
+                description: Some("Currents at the operating point".to_string()),
+                result: None,
+                operations: i_values,
+            }],
+        }
+    }
+}
+
+/// Current `Is·(e^(V/Vt) - 1)` and conductance `dI/dV` of a diode at a junction voltage.
+fn diode_model(element: &Element, voltage: f64) -> (f64, f64) {
+    let vt: f64 = element.thermal_voltage();
+    let exponential: f64 = (voltage / vt).exp();
+    (
+        element.value * (exponential - 1.0),
+        element.value * exponential / vt,
+    )
+}
+
+/// Limit the step of a forward biased junction voltage, as SPICE does (pnjlim).
+///
+/// Large steps past the knee of the curve are taken logarithmically so the exponential
+/// does not overflow before the iteration settles.
+fn limit_junction(element: &Element, new: f64, old: f64) -> f64 {
+    let vt: f64 = element.thermal_voltage();
+    let critical: f64 = vt * (vt / (std::f64::consts::SQRT_2 * element.value)).ln();
+    if new <= critical || (new - old).abs() <= 2.0 * vt {
+        return new;
+    }
+    if old > 0.0 {
+        let arg: f64 = 1.0 + (new - old) / vt;
+        match arg > 0.0 {
+            true => old + vt * arg.ln(),
+            false => critical,
+        }
+    } else {
+        vt * (new / vt).ln()
+    }
+}
+
+fn stamp_conductance(matrix: &mut DMatrix<f64>, p: Option<usize>, q: Option<usize>, g: f64) {
+    if let Some(p) = p {
+        matrix[(p, p)] += g;
+    }
+    if let Some(q) = q {
+        matrix[(q, q)] += g;
+    }
+    if let (Some(p), Some(q)) = (p, q) {
+        matrix[(p, q)] -= g;
+        matrix[(q, p)] -= g;
+    }
+}
+
+/// Current flowing from the positive node through the element to the negative node.
+fn stamp_current(rhs: &mut DVector<f64>, p: Option<usize>, q: Option<usize>, current: f64) {
+    if let Some(p) = p {
+        rhs[p] -= current;
+    }
+    if let Some(q) = q {
+        rhs[q] += current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::Component::{Diode, Ground, Resistor, VoltageSrc};
+    use crate::container::Container;
+    use crate::elements::Element;
+    use crate::solvers::nonlinear_node_solver::NonlinearNodeSolver;
+    use crate::solvers::solver::Solver;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_diode_operating_point() {
+        let mut c = Container::new();
+        c.add_element_no_id(Element::new(Ground, 0., vec![1, 3], vec![]));
+        c.add_element_no_id(Element::new(VoltageSrc, 5., vec![2], vec![0, 3]));
+        c.add_element_no_id(Element::new(Resistor, 1000., vec![1], vec![3]));
+        c.add_element_no_id(
+            Element::new(Diode, 1e-14, vec![2], vec![0, 1]).with_thermal_voltage(0.025852),
+        );
+        c.create_nodes().unwrap();
+
+        let mut solver: NonlinearNodeSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let steps = solver.solve().unwrap();
+        assert_eq!(steps.len(), 2);
+        assert!(solver.iterations.len() > 1);
+
+        // Vd solves (5 - Vd) / 1k = Is·(e^(Vd/Vt) - 1)
+        assert!((solver.node_voltages[0] - 5.0).abs() < 1e-9);
+        assert!((solver.node_voltages[1] - 0.692544).abs() < 1e-5);
+        let diode: f64 = solver.current_through(3).unwrap();
+        let resistor: f64 = solver.current_through(2).unwrap();
+        assert!((diode - 0.004307).abs() < 1e-5);
+        assert!((diode - resistor).abs() < 1e-9);
+    }
+}
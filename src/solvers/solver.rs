@@ -20,6 +20,108 @@ pub trait Solver {
 pub enum SolverType {
     NodeMatrix,
     NodeStep,
+    MeshMatrix,
+    MeshStep,
+}
+
+/// What a solver can handle, so a front end only offers applicable ones
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Capabilities {
+    pub voltage_sources: bool,
+    pub current_sources: bool,
+    pub supernodes: bool,
+    pub nonlinear: bool,
+    pub requires_planar: bool,
+    pub step_by_step: bool, // Explains the solve instead of only giving the matrices
+}
+
+/// Every solver that `SolverType` can select.
+pub fn supported_solvers() -> Vec<SolverType> {
+    vec![
+        SolverType::NodeMatrix,
+        SolverType::NodeStep,
+        SolverType::MeshMatrix,
+        SolverType::MeshStep,
+    ]
+}
+
+/// Nodal solvers handle supernodes and current sources.
+///
+/// Mesh solvers need a planar circuit.
+pub fn solver_capabilities(solver_type: SolverType) -> Capabilities {
+    let nodal: bool = matches!(solver_type, SolverType::NodeMatrix | SolverType::NodeStep);
+    Capabilities {
+        voltage_sources: true,
+        current_sources: nodal,
+        supernodes: nodal,
+        nonlinear: false,
+        requires_planar: !nodal,
+        step_by_step: matches!(solver_type, SolverType::NodeStep | SolverType::MeshStep),
+    }
+}
+
+/// Language of the step titles and descriptions
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+/// (Key, English, Spanish) for every translated step title and description
+const STEP_TEXT: [(&str, &str, &str); 23] = [
+    ("kcl_equations", "KCL Equations", "Ecuaciones LCK"),
+    (
+        "system_of_equations",
+        "System of Equations",
+        "Sistema de Ecuaciones",
+    ),
+    (
+        "connection_matrix",
+        "Connection Matrix",
+        "Matriz de Conexiones",
+    ),
+    (
+        "solve_node_voltages",
+        "Solve For Node Voltages",
+        "Resolver los Voltajes de Nodo",
+    ),
+    ("currents", "Currents", "Corrientes"),
+    (
+        "current_results",
+        "Current Results",
+        "Resultados de Corriente",
+    ),
+    ("kcl_equations_description", "Outline the basis of the circuit using KCL equations", "Plantear la base del circuito con ecuaciones LCK"),
+    ("mark_nodes", "Mark Nodes", "Marcar Nodos"),
+    ("mark_supernodes", "Mark Supernodes", "Marcar Supernodos"),
+    ("node_currents", "Current entering and exiting each node.", "Corriente que entra y sale de cada nodo."),
+    ("unsimplified_potentials", "Potential difference over each element as assembled, before simplification.", "Diferencia de potencial en cada elemento tal como se arma, antes de simplificar."),
+    ("element_currents", "Use potential difference between nodes ($ N_{j, k} $) and Ohm's law to solve for current. Where $j, k$ are the two nodes that the element is connected to. We can treat GND as 0.", "Usar la diferencia de potencial entre nodos ($ N_{j, k} $) y la ley de Ohm para hallar la corriente. Donde $j, k$ son los dos nodos a los que se conecta el elemento. Podemos tratar GND como 0."),
+    ("system_of_equations_description", "Write the KCL equations with the node voltages as the unknowns.", "Escribir las ecuaciones LCK con los voltajes de nodo como incógnitas."),
+    ("node_equations", "One equation for each node", "Una ecuación por cada nodo"),
+    ("supernode_sources", "Voltage sources inside supernodes", "Fuentes de voltaje dentro de supernodos"),
+    ("kcl_coefficients", "Coefficients from the expanded KCL equations", "Coeficientes de las ecuaciones LCK expandidas"),
+    ("element_connections", "Element connections between nodes.", "Conexiones de elementos entre nodos."),
+    ("matrix_rows", "The first row is the combined KCL equation, every other row is the constraint of one voltage source.", "La primera fila es la ecuación LCK combinada, cada otra fila es la restricción de una fuente de voltaje."),
+    ("invert_matrix", "Invert the matrix", "Invertir la matriz"),
+    ("multiply_inverse", "Multiply the inverted matrix by the source voltages", "Multiplicar la matriz invertida por los voltajes de las fuentes"),
+    ("currents_ohms_law", "Use potential difference between nodes ($ N_j $) and Ohm's law to solve for current.", "Usar la diferencia de potencial entre nodos ($ N_j $) y la ley de Ohm para hallar la corriente."),
+    ("scaled_currents", "Currents scaled to a readable unit.", "Corrientes escaladas a una unidad legible."),
+    ("currents_description", "Evaluate the currents using the KCL equations and node voltages shown previously.", "Evaluar las corrientes con las ecuaciones LCK y los voltajes de nodo mostrados antes."),
+];
+
+impl Locale {
+    /// Text for a step key in this language, the key itself if it is not in the table.
+    pub fn text(&self, key: &str) -> String {
+        match STEP_TEXT.iter().find(|x| x.0 == key) {
+            Some((_, english, spanish)) => match self {
+                Locale::English => english.to_string(),
+                Locale::Spanish => spanish.to_string(),
+            },
+            None => key.to_string(),
+        }
+    }
 }
 
 pub struct Step {
@@ -36,6 +138,14 @@ pub struct SubStep {
     pub operations: Vec<Operation>,
 }
 
+/// A single solved quantity, flattened for display in a results table
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SolutionRow {
+    pub quantity_name: String,
+    pub value: f64,
+    pub unit: String,
+}
+
 impl Step {
     pub fn new(label: &str) -> Self {
         Step {
@@ -190,15 +300,64 @@ pub fn serialize_steps(steps: Vec<Step>) -> Result<String, String> {
     }
 }
 
+/// Render steps as Markdown for reports, every result and operation gets a `$$` block.
+pub fn steps_to_markdown(steps: &[Step]) -> String {
+    let mut output: String = String::new();
+    for step in steps {
+        output.push_str(&format!("## {}\n\n", step.title().unwrap_or_default()));
+        if let Some(description) = step.description() {
+            output.push_str(&format!("{}\n\n", description));
+        }
+        if let Some(result) = &step.result {
+            output.push_str(&math_block(result));
+        }
+        for sub_step in step.sub_steps.iter() {
+            if let Some(description) = sub_step.description() {
+                output.push_str(&format!("### {}\n\n", description));
+            }
+            for operation in sub_step.operations.iter() {
+                output.push_str(&math_block(operation));
+            }
+            if let Some(result) = &sub_step.result {
+                output.push_str(&math_block(result));
+            }
+        }
+    }
+    output
+}
+
+fn math_block(op: &Operation) -> String {
+    format!("$$\n{}\n$$\n\n", op.latex_string())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::solvers::node_matrix_solver::NodeMatrixSolver;
     use crate::solvers::node_step_solver::NodeStepSolver;
-    use crate::solvers::solver::Solver;
+    use crate::solvers::solver::{
+        solver_capabilities, steps_to_markdown, supported_solvers, Capabilities, Solver, SolverType,
+    };
     use crate::util::create_mna_container;
     use std::cell::RefCell;
     use std::rc::Rc;
 
+    #[test]
+    fn test_solver_capabilities() {
+        assert_eq!(supported_solvers().len(), 4);
+        let node_step: Capabilities = solver_capabilities(SolverType::NodeStep);
+        assert!(node_step.supernodes);
+        assert!(node_step.voltage_sources);
+        assert!(node_step.step_by_step);
+        assert!(!node_step.nonlinear);
+        assert!(node_step.current_sources);
+        assert!(solver_capabilities(SolverType::NodeMatrix).current_sources);
+
+        let mesh: Capabilities = solver_capabilities(SolverType::MeshMatrix);
+        assert!(!mesh.current_sources);
+        assert!(mesh.requires_planar);
+        assert!(!mesh.step_by_step);
+    }
+
     #[test]
     fn test_solve_steps() {
         let mut c = create_mna_container();
@@ -221,4 +380,31 @@ mod tests {
             println!("---- Step ---- \n{}", i);
         }
     }
+
+    #[test]
+    fn test_steps_to_markdown() {
+        let mut c = create_mna_container();
+        c.create_nodes().unwrap();
+        c.create_super_nodes().unwrap();
+        let mut solver: NodeStepSolver = Solver::new(Rc::new(RefCell::new(c)));
+        let steps = solver.solve().unwrap();
+        let markdown: String = steps_to_markdown(&steps);
+
+        let headings = markdown.lines().filter(|x| x.starts_with("## ")).count();
+        assert_eq!(headings, steps.len());
+
+        let blocks: usize = steps
+            .iter()
+            .map(|step| {
+                step.result.iter().count()
+                    + step
+                        .sub_steps
+                        .iter()
+                        .map(|x| x.operations.len() + x.result.iter().count())
+                        .sum::<usize>()
+            })
+            .sum();
+        assert!(steps.iter().any(|x| x.result.is_some()));
+        assert_eq!(markdown.lines().filter(|x| *x == "$$").count(), 2 * blocks);
+    }
 }
@@ -7,7 +7,8 @@ use crate::validation::StatusError::{Known, Multiple};
 use crate::validation::{check_weak_duplicates, StatusError, Validation, ValidationResult};
 use operations::prelude::EquationMember;
 use petgraph::graph::UnGraph;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use std::cell::RefCell;
 use std::fmt::{Display, Formatter};
 use std::rc::{Rc, Weak};
@@ -24,12 +25,38 @@ pub enum ToolType {
 /// Tools are used to solve circuits
 ///
 /// Representation of a Tool (Node, Mesh, SuperNode, SuperMesh)
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Clone)]
 pub struct Tool {
     pub(crate) id: usize,
     pub(crate) class: ToolType,
     pub(crate) members: Vec<Weak<RefCell<Element>>>,
     pub(crate) value: f64,
+    pub(crate) terminals: Option<(usize, usize)>, // (Positive, Negative) node of a supernode's source
+    pub(crate) label: Option<String>,             // Shown in place of the id, e.g. VOUT
+}
+
+/// Members are written as element ids, the value is null until it has been solved. The
+/// label is only written when one is set.
+impl Serialize for Tool {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let value: Option<f64> = match self.value.is_finite() {
+            true => Some(self.value),
+            false => None,
+        };
+        let mut state = serializer.serialize_struct("Tool", 5)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("type", &self.class)?;
+        state.serialize_field("members", &self.member_ids())?;
+        state.serialize_field("value", &value)?;
+        match &self.label {
+            Some(label) => state.serialize_field("label", label)?,
+            None => state.skip_field("label")?,
+        }
+        state.end()
+    }
 }
 
 pub struct ToolIterator {
@@ -73,9 +100,15 @@ impl Tool {
         Tool::create(Node, elements)
     }
 
-    /// Create a supernode from the elements
-    pub(crate) fn create_supernode(elements: Vec<Weak<RefCell<Element>>>) -> Tool {
-        Tool::create(SuperNode, elements)
+    /// Create a supernode from the elements, with the nodes on the source's positive and
+    /// negative sides when they are known
+    pub(crate) fn create_supernode(
+        elements: Vec<Weak<RefCell<Element>>>,
+        terminals: Option<(usize, usize)>,
+    ) -> Tool {
+        let mut tool = Tool::create(SuperNode, elements);
+        tool.terminals = terminals;
+        tool
     }
 
     fn create(class: ToolType, elements: Vec<Weak<RefCell<Element>>>) -> Tool {
@@ -84,6 +117,8 @@ impl Tool {
             class,
             members: vec![],
             value: f64::NAN,
+            terminals: None,
+            label: None,
         };
         tool.members = elements;
         tool
@@ -107,7 +142,10 @@ impl Tool {
             })
     }
 
-    fn node_edges(nodes: &Vec<Weak<RefCell<Tool>>>) -> Result<Vec<(u32, u32)>, StatusError> {
+    fn node_edges(
+        nodes: &Vec<Weak<RefCell<Tool>>>,
+        ground: usize,
+    ) -> Result<Vec<(u32, u32)>, StatusError> {
         // If no nodes are present, return an error
         if !nodes.iter().any(|p| {
             return if let Some(x) = p.upgrade() {
@@ -130,7 +168,7 @@ impl Tool {
                     .members
                     .iter()
                     .filter_map(|x| x.upgrade())
-                    .any(|x| x.borrow().connected_to_ground())
+                    .any(|x| x.borrow().connected_to_ground(ground))
                 {
                     let x = (node.borrow().id as u32, 0);
                     if !edges.contains(&x) && (x.0 != x.1) {
@@ -164,8 +202,9 @@ impl Tool {
 
     pub fn nodes_to_graph(
         nodes: &Vec<Weak<RefCell<Tool>>>,
+        ground: usize,
     ) -> Result<UnGraph<i32, ()>, StatusError> {
-        let edges: Vec<(u32, u32)> = Tool::node_edges(nodes)?;
+        let edges: Vec<(u32, u32)> = Tool::node_edges(nodes, ground)?;
         Ok(UnGraph::<i32, ()>::from_edges(edges.as_slice()))
     }
 
@@ -177,6 +216,11 @@ impl Tool {
             .collect()
     }
 
+    /// Members that are still alive, as element references.
+    pub fn member_elements(&self) -> Vec<Rc<RefCell<Element>>> {
+        self.members.iter().filter_map(|x| x.upgrade()).collect()
+    }
+
     pub fn members_weak(&self) -> Vec<Weak<RefCell<Element>>> {
         self.members.clone()
     }
@@ -205,6 +249,9 @@ impl EquationMember for Tool {
     }
 
     fn latex_string(&self) -> String {
+        if let Some(label) = &self.label {
+            return format!("\\text{{{}}}", escape_tex(label));
+        }
         match self.class {
             Node => format!("N_{{{}}}", self.id),
             Mesh => format!("M_{{{}}}", self.id),
@@ -314,16 +361,37 @@ impl PrettyPrint for Tool {
     }
 }
 
+/// Escape the TeX special characters so any label can go inside `\text{...}`.
+fn escape_tex(text: &str) -> String {
+    text.chars()
+        .map(|x| match x {
+            '\\' => "\\textbackslash{}".to_string(),
+            '^' => "\\textasciicircum{}".to_string(),
+            '~' => "\\textasciitilde{}".to_string(),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => format!("\\{}", x),
+            _ => x.to_string(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::component::Component::Resistor;
     use crate::container::Container;
+    use crate::elements::Element;
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::Solver;
     use crate::tools::{Tool, ToolType};
-    use crate::util::{create_basic_container, create_basic_supermesh_container};
+    use crate::util::{
+        create_basic_container, create_basic_supermesh_container, create_mna_container,
+    };
     use crate::validation::StatusError::Known;
     use crate::validation::Validation;
+    use operations::prelude::EquationMember;
     use petgraph::graph::UnGraph;
+    use serde_json::json;
     use std::cell::RefCell;
-    use std::rc::Weak;
+    use std::rc::{Rc, Weak};
 
     #[test]
     fn test_validate() {
@@ -334,11 +402,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize() {
+        let mut container: Container = create_mna_container();
+        container.create_nodes().unwrap();
+        let unsolved = container.get_tool_by_id(0).borrow().clone();
+        assert_eq!(
+            serde_json::to_value(&unsolved).unwrap()["value"],
+            json!(null)
+        );
+
+        container.create_super_nodes().unwrap();
+        let container = Rc::new(RefCell::new(container));
+        let mut solver: NodeStepSolver = Solver::new(container.clone());
+        solver.solve().unwrap();
+        let node = container.borrow().get_tool_by_id(0).borrow().clone();
+        assert_eq!(
+            serde_json::to_value(&node).unwrap(),
+            json!({"id": 1, "type": "Node", "members": [5, 2], "value": 20.0})
+        );
+    }
+
+    #[test]
+    fn test_label_escape() {
+        let mut container: Container = create_mna_container();
+        container.create_nodes().unwrap();
+        container.label_node(1, "V_OUT & 50%").unwrap();
+        container.label_node(2, "{a}^~\\$#").unwrap();
+
+        let node = container.get_tool_by_id(0).borrow().clone();
+        assert_eq!(node.latex_string(), "\\text{V\\_OUT \\& 50\\%}");
+        let node = container.get_tool_by_id(1).borrow().clone();
+        assert_eq!(
+            node.latex_string(),
+            "\\text{\\{a\\}\\textasciicircum{}\\textasciitilde{}\\textbackslash{}\\$\\#}"
+        );
+        // The label itself is kept as given
+        assert_eq!(node.label, Some("{a}^~\\$#".to_string()));
+    }
+
+    #[test]
+    fn test_member_elements() {
+        let mut basic: Container = create_basic_container();
+        let nodes: Vec<Weak<RefCell<Tool>>> = basic.create_nodes().unwrap().nodes();
+        let node = nodes[1].upgrade().unwrap();
+        let members: Vec<Rc<RefCell<Element>>> = node.borrow().member_elements();
+        assert_eq!(members.len(), 2);
+        assert!(members.iter().all(|x| x.borrow().class == Resistor));
+        assert!(Rc::ptr_eq(&members[0], basic.get_element_by_id(1).unwrap()));
+        assert!(Rc::ptr_eq(&members[1], basic.get_element_by_id(2).unwrap()));
+
+        let element = Rc::new(RefCell::new(Element::new(Resistor, 1.0, vec![1], vec![2])));
+        let mut tool = Tool::create_node(vec![Rc::downgrade(&element)]);
+        tool.members.push(Rc::downgrade(&Rc::new(RefCell::new(
+            element.borrow().clone(),
+        ))));
+        assert_eq!(tool.member_elements().len(), 1);
+    }
+
     #[test]
     fn test_create_node_graph() {
         let mut basic: Container = create_basic_container();
         let container: Vec<Weak<RefCell<Tool>>> = basic.create_nodes().unwrap().nodes();
-        let edges = Tool::node_edges(&container).unwrap();
+        let edges = Tool::node_edges(&container, 0).unwrap();
         let expected = vec![(1, 0), (1, 2), (2, 0)];
 
         assert_eq!(edges.len(), expected.len());
@@ -347,13 +473,13 @@ mod tests {
         }
 
         let container: Vec<Weak<RefCell<Tool>>> = basic.create_nodes().unwrap().nodes();
-        let graph = Tool::nodes_to_graph(&container).unwrap();
+        let graph = Tool::nodes_to_graph(&container, 0).unwrap();
         assert_eq!(graph.node_count(), 3);
         assert_eq!(graph.edge_count(), 3);
 
         let mut super_node = create_basic_supermesh_container();
         let graph: UnGraph<i32, ()> =
-            Tool::nodes_to_graph(&super_node.create_nodes().unwrap().nodes()).unwrap();
+            Tool::nodes_to_graph(&super_node.create_nodes().unwrap().nodes(), 0).unwrap();
         println!("{:?}", graph);
         assert_eq!(graph.node_count(), 5);
         assert_eq!(graph.edge_count(), 7);
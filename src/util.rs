@@ -77,15 +77,160 @@ pub fn create_mna_container_2() -> Container {
     container
 }
 
+/// The circuit of the case_1 test data, V3 holds N2 at 10 V and I5 draws 5 A from N1 into N2.
+#[allow(dead_code)]
+pub fn create_current_source_container() -> Container {
+    let mut container = Container::new();
+    container.add_element_no_id(Element::new(Ground, 0., vec![3, 2, 1], vec![]));
+    container.add_element_no_id(Element::new(Resistor, 2., vec![4, 5], vec![0]));
+    container.add_element_no_id(Element::new(Resistor, 6., vec![3, 4, 5], vec![0]));
+    container.add_element_no_id(Element::new(VoltageSrc, 10., vec![2, 4, 5], vec![0]));
+    container.add_element_no_id(Element::new(Resistor, 4., vec![1, 5], vec![2, 3, 5]));
+    container.add_element_no_id(Element::new(CurrentSrc, 5., vec![1, 4], vec![2, 3, 4]));
+    container
+}
+
+/// Source over two resistors in series, the node between them is the divider output.
+///
+/// Element 2 runs from the source to the output, element 3 from the output to ground.
+#[allow(dead_code)]
+pub fn create_divider_container(source: f64, top: f64, bottom: f64) -> Container {
+    let mut container = Container::new();
+    container.add_element_no_id(Element::new(Ground, 0., vec![1, 3], vec![]));
+    container.add_element_no_id(Element::new(VoltageSrc, source, vec![2], vec![0]));
+    container.add_element_no_id(Element::new(Resistor, top, vec![1], vec![3]));
+    container.add_element_no_id(Element::new(Resistor, bottom, vec![2], vec![0]));
+    container
+}
+
+/// Check that every solved resistor absorbs power, its current and voltage drop are measured
+/// in the same direction across its node pair and agree with the node voltages.
+#[cfg(test)]
+pub(crate) fn assert_passive_sign_convention(container: &Container) {
+    for element in container.get_elements().iter().map(|x| x.borrow()) {
+        if element.class != Resistor || !element.enabled {
+            continue;
+        }
+        let across: f64 = container
+            .voltage_across(element.id)
+            .expect("Container has to be solved");
+        let current: f64 = container.current_through(element.id).unwrap();
+        let name: String = element.basic_string();
+        assert!(
+            (element.voltage_drop - across).abs() < 1e-9,
+            "{} drops {} V but its nodes differ by {} V",
+            name,
+            element.voltage_drop,
+            across
+        );
+        assert!(
+            (current * element.value - across).abs() < 1e-9,
+            "{} carries {} A against a drop of {} V",
+            name,
+            current,
+            across
+        );
+        assert!(current * across >= 0.0, "{} delivers power", name);
+    }
+}
+
+/// Solve copies of a container with every solver that supports it and check that the node
+/// voltages, voltage source currents and resistor currents they report agree within `tol`.
+///
+/// The mesh solvers only take part for planar circuits without current sources.
+#[cfg(test)]
+pub(crate) fn assert_solvers_agree(container: &Container, tol: f64) {
+    use crate::solvers::mesh_matrix_solver::MeshMatrixSolver;
+    use crate::solvers::mesh_step_solver::MeshStepSolver;
+    use crate::solvers::node_matrix_solver::NodeMatrixSolver;
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::{solver_capabilities, Solver, SolverType};
+    use nalgebra::DVector;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let fresh = || -> Rc<RefCell<Container>> {
+        let mut copy: Container = container.clone_with_id_offset(0);
+        copy.create_nodes().unwrap();
+        copy.create_super_nodes().unwrap();
+        Rc::new(RefCell::new(copy))
+    };
+    let agree = |name: &str, expected: &DVector<f64>, actual: &DVector<f64>| {
+        assert_eq!(expected.len(), actual.len(), "{} sizes differ", name);
+        assert!(
+            (expected - actual).abs().max() <= tol,
+            "{} differ: {} vs {}",
+            name,
+            expected,
+            actual
+        );
+    };
+    // Source currents by element id, the solvers list the sources in different orders
+    let by_id = |mut currents: Vec<(usize, f64)>| -> (Vec<usize>, DVector<f64>) {
+        currents.sort_by_key(|x| x.0);
+        let (ids, values): (Vec<usize>, Vec<f64>) = currents.into_iter().unzip();
+        (ids, DVector::from_vec(values))
+    };
+
+    let step_container = fresh();
+    let mut step: NodeStepSolver = Solver::new(step_container.clone());
+    step.solve().expect("NodeStep failed");
+    let voltages: DVector<f64> = DVector::from_vec(
+        step_container
+            .borrow()
+            .nodes()
+            .iter()
+            .map(|x| x.upgrade().unwrap().borrow().value)
+            .collect(),
+    );
+    let currents: DVector<f64> = step.branch_currents();
+
+    let mut matrix: NodeMatrixSolver = Solver::new(fresh());
+    matrix.solve().expect("NodeMatrix failed");
+    agree("NodeMatrix voltages", &voltages, &matrix.node_voltages());
+    // Sources joined in a loop are not fixed by the node solve, only NodeMatrix has them
+    if let Ok(step_sources) = step.source_currents() {
+        let (step_ids, step_sources) = by_id(step_sources);
+        let (matrix_ids, matrix_sources) = by_id(matrix.source_currents());
+        assert_eq!(step_ids, matrix_ids, "Source ids differ");
+        agree("NodeMatrix source currents", &step_sources, &matrix_sources);
+    }
+
+    let mesh = solver_capabilities(SolverType::MeshStep);
+    let has_current_sources: bool = container
+        .get_elements()
+        .iter()
+        .any(|x| x.borrow().class == CurrentSrc);
+    let applicable: bool = (mesh.current_sources || !has_current_sources)
+        && (!mesh.requires_planar || fresh().borrow().is_planar());
+    if applicable {
+        let mut mesh_step: MeshStepSolver = Solver::new(fresh());
+        mesh_step.solve().expect("MeshStep failed");
+        agree("MeshStep currents", &currents, &mesh_step.branch_currents());
+
+        let mut mesh_matrix: MeshMatrixSolver = Solver::new(fresh());
+        mesh_matrix.solve().expect("MeshMatrix failed");
+        agree(
+            "MeshMatrix currents",
+            &currents,
+            &mesh_matrix.branch_currents(),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::container::Container;
     use crate::elements::Element;
+    use crate::solvers::node_step_solver::NodeStepSolver;
+    use crate::solvers::solver::Solver;
     use crate::util::*;
     use crate::validation::Status::Valid;
     use crate::validation::Validation;
     use assert_json_diff::assert_json_include;
     use serde_json::json;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     #[test]
     fn test_create_containers() {
@@ -95,6 +240,7 @@ mod tests {
             create_basic_supermesh_container(),
             create_mna_container(),
             create_mna_container_2(),
+            create_current_source_container(),
         ];
 
         let mut id: usize = 0;
@@ -113,6 +259,28 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_passive_sign_convention() {
+        for container in [create_mna_container(), create_basic_container()] {
+            let mut container: Container = container;
+            container.create_nodes().unwrap();
+            container.create_super_nodes().unwrap();
+            let container = Rc::new(RefCell::new(container));
+            let mut solver: NodeStepSolver = Solver::new(container.clone());
+            solver.solve().unwrap();
+            assert_passive_sign_convention(&container.borrow());
+        }
+    }
+
+    #[test]
+    fn test_solvers_agree() {
+        assert_solvers_agree(&create_mna_container(), 1e-9);
+        assert_solvers_agree(&create_basic_container(), 1e-9);
+        // Current sources, into a supernode and beside a source held to ground
+        assert_solvers_agree(&create_mna_container_2(), 1e-9);
+        assert_solvers_agree(&create_current_source_container(), 1e-9);
+    }
+
     #[test]
     fn test_serde() {
         let json = json!({
@@ -132,6 +300,13 @@ mod tests {
             class: Resistor,
             positive: vec![2],
             negative: vec![3],
+            temperature_coefficient: None,
+            nominal_value: None,
+            thermal_voltage: None,
+            number: None,
+            waveform: None,
+            enabled: true,
+            position: None,
         };
         assert_eq!(element.name, "R1");
         assert_json_include!(actual: element, expected: json);
@@ -28,6 +28,16 @@ pub enum StatusError {
 
 pub type ValidationResult = Result<Status, StatusError>;
 
+/// How strictly `Container::validate_with_mode` checks a circuit
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum ValidationMode {
+    /// Only reject circuits that can not be solved, the same as `validate`
+    #[default]
+    Lenient,
+    /// Also reject shorted elements, dead-end branches and values outside the typical range
+    Strict,
+}
+
 pub trait Validation {
     fn validate(&self) -> ValidationResult;
     fn clean(&mut self) -> &Self {